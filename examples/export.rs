@@ -0,0 +1,28 @@
+//! Exports a graphic of a wad as PNG, e.g.
+//! `cargo run --example export -- doom1.wad map E1M1 e1m1.png`.
+use doom::export::Exporter;
+use doom::wad::Wad;
+use std::env;
+use std::error::Error;
+use std::fs;
+
+/// The longer side of exported maps in pixels.
+const MAP_SIZE: usize = 2048;
+
+fn main() -> Result<(), Box<dyn Error>> {
+  let args: Vec<String> = env::args().collect();
+  if args.len() != 5 {
+    return Err("usage: export <wad> <picture|texture|flat|map> <name> <png>".into());
+  }
+  let wad = Wad::new(&fs::read(&args[1])?)?;
+  let exporter = Exporter::new(&wad)?;
+  let png = match args[2].as_str() {
+    "picture" => exporter.picture(&args[3])?,
+    "texture" => exporter.texture(&args[3])?,
+    "flat" => exporter.flat(&args[3])?,
+    "map" => exporter.map(&args[3], MAP_SIZE)?,
+    kind => return Err(format!("unknown kind {}", kind).into()),
+  };
+  fs::write(&args[4], png)?;
+  Ok(())
+}
@@ -19,6 +19,10 @@ impl Angle {
     }
   }
 
+  pub fn degrees(self) -> f32 {
+    self.0
+  }
+
   pub fn update(&mut self, float: f32) {
     self.0 = float;
     self.normalize();
@@ -46,3 +46,13 @@ pub struct Rotation {
   pub angle: Angle,
   pub fov: i16,
 }
+
+/// Things are drawn with the frames of their sprite, e.g. `TROO` frame `A`,
+/// rotated by the angle the thing is facing.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct Sprite {
+  pub name: String,
+  pub frame: char,
+  pub angle: Angle,
+}
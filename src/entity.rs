@@ -1,10 +1,10 @@
 use crate::angle::Angle;
-use crate::component::{Direction, KeyboardControlled, Position, Rotation, Velocity};
+use crate::component::{Direction, KeyboardControlled, Position, Rotation, Sprite, Velocity};
 use crate::datatypes::Result;
 use crate::errors::DoomError;
 use crate::wad::thing::Thing;
 use crate::wad::Wad;
-use specs::{Builder, World, WorldExt};
+use specs::{Builder, Join, World, WorldExt};
 
 pub fn create_player(map: &str, id: u16, wad: &Wad, world: &mut World) -> Result<()> {
   match wad.find_map_index(map) {
@@ -31,9 +31,224 @@ pub fn create_player(map: &str, id: u16, wad: &Wad, world: &mut World) -> Result
             .build();
           Ok(())
         }
-        None => Err(DoomError::Wad(format!("Failed to load THINGS: {}", id))),
+        None => Err(DoomError::ThingNotFound {
+          map: String::from(map),
+          typ: id,
+        }),
       }
     }
-    None => Err(DoomError::Wad(format!("Failed to load THINGS: {}", id))),
+    None => Err(DoomError::MapNotFound(String::from(map))),
+  }
+}
+
+/// Things which only appear on the easier or harder skill levels, or in
+/// multiplayer games, are flagged in `Thing::flags`.
+const SKILL_MEDIUM: u16 = 0x0002;
+const MULTIPLAYER_ONLY: u16 = 0x0010;
+
+/// Spawns every thing of a map which has a sprite. The player starts,
+/// teleport destinations and monster spawners stay invisible.
+pub fn create_things(map: &str, wad: &Wad, world: &mut World) -> Result<()> {
+  match wad.find_map_index(map) {
+    Some(map_index) => {
+      let things = wad.read_wad_for::<Thing>(map_index)?;
+      // Things of a previously loaded map.
+      let entities = (&world.entities(), &world.read_storage::<Sprite>())
+        .join()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+      world.delete_entities(&entities).unwrap();
+
+      for thing in things {
+        if thing.flags & SKILL_MEDIUM == 0 || thing.flags & MULTIPLAYER_ONLY != 0 {
+          continue;
+        }
+        if let Some((name, frame)) = thing_sprite(thing.typ) {
+          world
+            .create_entity()
+            .with(Position {
+              x: thing.x,
+              y: thing.y,
+            })
+            .with(Sprite {
+              name: String::from(name),
+              frame,
+              angle: Angle::new(thing.direction as f32),
+            })
+            .build();
+        }
+      }
+      Ok(())
+    }
+    None => Err(DoomError::MapNotFound(String::from(map))),
+  }
+}
+
+/// The sprite and frame a thing type is drawn with.
+fn thing_sprite(typ: u16) -> Option<(&'static str, char)> {
+  let sprite = match typ {
+    // Monsters
+    7 => ("SPID", 'A'),
+    9 => ("SPOS", 'A'),
+    16 => ("CYBR", 'A'),
+    58 | 3002 => ("SARG", 'A'),
+    64 => ("VILE", 'A'),
+    65 => ("CPOS", 'A'),
+    66 => ("SKEL", 'A'),
+    67 => ("FATT", 'A'),
+    68 => ("BSPI", 'A'),
+    69 => ("BOS2", 'A'),
+    71 => ("PAIN", 'A'),
+    72 => ("KEEN", 'A'),
+    84 => ("SSWV", 'A'),
+    88 => ("BBRN", 'A'),
+    3001 => ("TROO", 'A'),
+    3003 => ("BOSS", 'A'),
+    3004 => ("POSS", 'A'),
+    3005 => ("HEAD", 'A'),
+    3006 => ("SKUL", 'A'),
+    // Weapons
+    82 => ("SGN2", 'A'),
+    2001 => ("SHOT", 'A'),
+    2002 => ("MGUN", 'A'),
+    2003 => ("LAUN", 'A'),
+    2004 => ("PLAS", 'A'),
+    2005 => ("CSAW", 'A'),
+    2006 => ("BFUG", 'A'),
+    // Ammunition
+    8 => ("BPAK", 'A'),
+    17 => ("CELP", 'A'),
+    2007 => ("CLIP", 'A'),
+    2008 => ("SHEL", 'A'),
+    2010 => ("ROCK", 'A'),
+    2046 => ("BROK", 'A'),
+    2047 => ("CELL", 'A'),
+    2048 => ("AMMO", 'A'),
+    2049 => ("SBOX", 'A'),
+    // Health, armor and powerups
+    83 => ("MEGA", 'A'),
+    2011 => ("STIM", 'A'),
+    2012 => ("MEDI", 'A'),
+    2013 => ("SOUL", 'A'),
+    2014 => ("BON1", 'A'),
+    2015 => ("BON2", 'A'),
+    2018 => ("ARM1", 'A'),
+    2019 => ("ARM2", 'A'),
+    2022 => ("PINV", 'A'),
+    2023 => ("PSTR", 'A'),
+    2024 => ("PINS", 'A'),
+    2025 => ("SUIT", 'A'),
+    2026 => ("PMAP", 'A'),
+    2045 => ("PVIS", 'A'),
+    // Keys
+    5 => ("BKEY", 'A'),
+    6 => ("YKEY", 'A'),
+    13 => ("RKEY", 'A'),
+    38 => ("RSKU", 'A'),
+    39 => ("YSKU", 'A'),
+    40 => ("BSKU", 'A'),
+    // Obstacles and decorations
+    30 => ("COL1", 'A'),
+    31 => ("COL2", 'A'),
+    32 => ("COL3", 'A'),
+    33 => ("COL4", 'A'),
+    34 => ("CAND", 'A'),
+    35 => ("CBRA", 'A'),
+    36 => ("COL5", 'A'),
+    37 => ("COL6", 'A'),
+    41 => ("CEYE", 'A'),
+    42 => ("FSKU", 'A'),
+    43 => ("TRE1", 'A'),
+    44 => ("TBLU", 'A'),
+    45 => ("TGRN", 'A'),
+    46 => ("TRED", 'A'),
+    47 => ("SMIT", 'A'),
+    48 => ("ELEC", 'A'),
+    54 => ("TRE2", 'A'),
+    55 => ("SMBT", 'A'),
+    56 => ("SMGT", 'A'),
+    57 => ("SMRT", 'A'),
+    70 => ("FCAN", 'A'),
+    85 => ("TLMP", 'A'),
+    86 => ("TLP2", 'A'),
+    2028 => ("COLU", 'A'),
+    2035 => ("BAR1", 'A'),
+    // Hanging bodies
+    49 | 63 => ("GOR1", 'A'),
+    50 | 59 => ("GOR2", 'A'),
+    51 | 61 => ("GOR3", 'A'),
+    52 | 60 => ("GOR4", 'A'),
+    53 | 62 => ("GOR5", 'A'),
+    73 => ("HDB1", 'A'),
+    74 => ("HDB2", 'A'),
+    75 => ("HDB3", 'A'),
+    76 => ("HDB4", 'A'),
+    77 => ("HDB5", 'A'),
+    78 => ("HDB6", 'A'),
+    // Corpses and gore
+    10 | 12 => ("PLAY", 'W'),
+    15 => ("PLAY", 'N'),
+    18 => ("POSS", 'L'),
+    19 => ("SPOS", 'L'),
+    20 => ("TROO", 'M'),
+    21 => ("SARG", 'N'),
+    22 => ("HEAD", 'L'),
+    24 => ("POL5", 'A'),
+    25 => ("POL1", 'A'),
+    26 => ("POL6", 'A'),
+    27 => ("POL4", 'A'),
+    28 => ("POL2", 'A'),
+    29 => ("POL3", 'A'),
+    79 => ("POB1", 'A'),
+    80 => ("POB2", 'A'),
+    81 => ("BRS1", 'A'),
+    _ => return None,
+  };
+  Some(sprite)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::component::{Position, Sprite};
+  use crate::entity::create_things;
+  use crate::wad::tests::build_wad;
+  use crate::wad::Wad;
+  use specs::{Join, World, WorldExt};
+
+  fn thing(x: i16, typ: u16, flags: u16) -> Vec<u8> {
+    [x, 0, 90, typ as i16, flags as i16]
+      .iter()
+      .flat_map(|value| value.to_le_bytes().to_vec())
+      .collect()
+  }
+
+  #[test]
+  fn spawn_things_with_sprites() {
+    let things = [
+      thing(0, 1, 0x07),
+      thing(10, 3001, 0x07),
+      thing(20, 3001, 0x01),
+      thing(30, 9, 0x17),
+      thing(40, 2035, 0x06),
+      thing(50, 14, 0x07),
+    ]
+    .concat();
+    let wad = Wad::new(&build_wad("IWAD", &[("E1M1", &[]), ("THINGS", &things)])).unwrap();
+    let mut world = World::new();
+    world.register::<Position>();
+    world.register::<Sprite>();
+
+    for _ in 0..2 {
+      create_things("E1M1", &wad, &mut world).unwrap();
+      world.maintain();
+      let positions = world.read_storage::<Position>();
+      let sprites = world.read_storage::<Sprite>();
+      let mut spawned = (&positions, &sprites)
+        .join()
+        .map(|(pos, sprite)| (pos.x, sprite.name.as_str(), sprite.angle.degrees()))
+        .collect::<Vec<_>>();
+      spawned.sort_by_key(|&(x, _, _)| x);
+      assert_eq!(spawned, vec![(10, "TROO", 90.0), (40, "BAR1", 90.0)]);
+    }
   }
 }
@@ -1,3 +1,4 @@
+use crate::validation::Diagnostic;
 use std::array::TryFromSliceError;
 use std::error::Error;
 use std::fmt;
@@ -7,7 +8,67 @@ use std::string::FromUtf8Error;
 pub enum DoomError {
   Number(TryFromSliceError),
   String(FromUtf8Error),
-  Wad(String),
+  Png(png::EncodingError),
+  MapNotFound(String),
+  LumpMissing {
+    map: Option<String>,
+    lump: String,
+  },
+  LumpSizeMismatch {
+    lump: String,
+    size: usize,
+    record_size: usize,
+  },
+  InvalidIndex {
+    kind: &'static str,
+    index: usize,
+    max: usize,
+  },
+  ThingNotFound {
+    map: String,
+    typ: u16,
+  },
+  UnsupportedFormat(String),
+  InvalidMap {
+    map: String,
+    diagnostics: Vec<Diagnostic>,
+  },
+  /// Reading from `offset` up to the end offset `end` failed, because the
+  /// data is only `actual` bytes long.
+  Truncated {
+    lump: Option<String>,
+    offset: usize,
+    end: usize,
+    actual: usize,
+  },
+  PatchMissing {
+    texture: String,
+    patch: String,
+  },
+  InvalidSprite {
+    sprite: String,
+    cause: String,
+  },
+}
+
+impl DoomError {
+  /// Adds the name of the lump that was read to errors without one.
+  pub fn in_lump(self, name: &str) -> Self {
+    match self {
+      DoomError::Truncated {
+        lump: None,
+        offset,
+        end,
+        actual,
+      } => DoomError::Truncated {
+        lump: Some(String::from(name)),
+        offset,
+        end,
+        actual,
+      },
+      error => error,
+    }
+  }
 }
 
 impl fmt::Display for DoomError {
@@ -15,24 +76,80 @@ impl fmt::Display for DoomError {
     match *self {
       DoomError::Number(ref cause) => write!(f, "Convert error: {}", cause),
       DoomError::String(ref cause) => write!(f, "String error: {}", cause),
-      DoomError::Wad(ref cause) => write!(f, "Wad error: {}", cause),
+      DoomError::Png(ref cause) => write!(f, "PNG error: {}", cause),
+      DoomError::MapNotFound(ref map) => write!(f, "Wad error: MAP {} not found", map),
+      DoomError::LumpMissing {
+        map: Some(ref map),
+        ref lump,
+      } => write!(f, "Wad error: {} is missing for MAP {}", lump, map),
+      DoomError::LumpMissing {
+        map: None,
+        ref lump,
+      } => write!(f, "Wad error: {} is missing", lump),
+      DoomError::LumpSizeMismatch {
+        ref lump,
+        size,
+        record_size,
+      } => write!(
+        f,
+        "Wad error: {} has {} bytes which is no multiple of {}",
+        lump, size, record_size
+      ),
+      DoomError::InvalidIndex { kind, index, max } => write!(
+        f,
+        "Wad error: {} index {} is out of range, max is {}",
+        kind, index, max
+      ),
+      DoomError::ThingNotFound { ref map, typ } => {
+        write!(f, "Wad error: no THING of type {} in MAP {}", typ, map)
+      }
+      DoomError::UnsupportedFormat(ref cause) => write!(f, "Wad error: {}", cause),
+      DoomError::InvalidMap {
+        ref map,
+        ref diagnostics,
+      } => {
+        write!(f, "Map error: MAP {} is invalid", map)?;
+        for diagnostic in diagnostics {
+          write!(f, "\n{:?}: {}", diagnostic.severity, diagnostic.message)?;
+        }
+        Ok(())
+      }
+      DoomError::Truncated {
+        ref lump,
+        offset,
+        end,
+        actual,
+      } => write!(
+        f,
+        "Wad error: {} is truncated at offset {}, needs {} bytes but has {}",
+        lump.as_deref().unwrap_or("WAD"),
+        offset,
+        end,
+        actual
+      ),
+      DoomError::PatchMissing {
+        ref texture,
+        ref patch,
+      } => write!(
+        f,
+        "Wad error: patch {} of texture {} is missing",
+        patch, texture
+      ),
+      DoomError::InvalidSprite {
+        ref sprite,
+        ref cause,
+      } => write!(f, "Wad error: sprite {} {}", sprite, cause),
     }
   }
 }
 
 impl Error for DoomError {
-  fn description(&self) -> &str {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
     match *self {
-      DoomError::Number(ref cause) => cause.description(),
-      DoomError::String(ref cause) => cause.description(),
-      DoomError::Wad(ref cause) => cause.as_str(),
-    }
-  }
-  fn cause(&self) -> Option<&dyn Error> {
-    match &self {
       DoomError::Number(ref cause) => Some(cause),
       DoomError::String(ref cause) => Some(cause),
-      DoomError::Wad(ref _cause) => None,
+      DoomError::Png(ref cause) => Some(cause),
+      _ => None,
     }
   }
 }
@@ -48,3 +165,9 @@ impl From<FromUtf8Error> for DoomError {
     DoomError::String(cause)
   }
 }
+
+impl From<png::EncodingError> for DoomError {
+  fn from(cause: png::EncodingError) -> Self {
+    DoomError::Png(cause)
+  }
+}
@@ -0,0 +1,407 @@
+use crate::datatypes::Result;
+use crate::errors::DoomError;
+use crate::map::Map;
+use crate::render::flat::{Flats, FLAT_SIZE};
+use crate::render::texture::{Texture, Textures};
+use crate::wad::linedef::{LineDef, NO_SIDE_DEF};
+use crate::wad::picture::Picture;
+use crate::wad::playpal::PlayPal;
+use crate::wad::Wad;
+use png::{BitDepth, ColorType, Encoder};
+
+/// The automap colors of the original engine: one sided walls are red,
+/// steps brown, ceiling changes yellow and all other lines grey.
+const WALL_COLOR: u8 = 176;
+const FLOOR_CHANGE_COLOR: u8 = 64;
+const CEILING_CHANGE_COLOR: u8 = 231;
+const TWO_SIDED_COLOR: u8 = 96;
+const BACKGROUND_COLOR: u8 = 0;
+
+/// A palette indexed image, stored row by row. `None` is transparent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+  pub width: usize,
+  pub height: usize,
+  pub pixels: Vec<Option<u8>>,
+}
+
+impl Image {
+  pub fn new(width: usize, height: usize, color: Option<u8>) -> Self {
+    Image {
+      width,
+      height,
+      pixels: vec![color; width * height],
+    }
+  }
+
+  /// Everything between the posts stays transparent, posts of tall patches
+  /// reaching past the bottom are cut off.
+  pub fn from_picture(picture: &Picture) -> Self {
+    let mut image = Image::new(picture.width, picture.height, None);
+    for (x, posts) in picture.columns.iter().enumerate() {
+      for post in posts {
+        for (row, &pixel) in post.pixels.iter().enumerate() {
+          image.set(x, post.top_delta + row, pixel);
+        }
+      }
+    }
+    image
+  }
+
+  /// Rows of a column no patch covers are transparent.
+  pub fn from_texture(texture: &Texture) -> Self {
+    let mut image = Image::new(texture.width, texture.height, None);
+    for x in 0..texture.width {
+      let column = &texture.pixels[x * texture.height..(x + 1) * texture.height];
+      for &(top, bottom) in texture.opaque[x].iter() {
+        for (row, &pixel) in column[top..bottom].iter().enumerate() {
+          image.set(x, top + row, pixel);
+        }
+      }
+    }
+    image
+  }
+
+  pub fn from_flat(flat: &[u8]) -> Self {
+    Image {
+      width: FLAT_SIZE,
+      height: FLAT_SIZE,
+      pixels: flat.iter().map(|&pixel| Some(pixel)).collect(),
+    }
+  }
+
+  /// Draws all linedefs from above, with north up, so that the longer side
+  /// of the map is `size` pixels long.
+  pub fn from_map(map: &Map, size: usize) -> Self {
+    let (x_min, y_min, x_max, y_max) = map.bounds();
+    if map.vertexes().is_empty() || size == 0 {
+      return Image::new(1, 1, Some(BACKGROUND_COLOR));
+    }
+    // Maps can span more than the range of an i16.
+    let map_size = (i32::from(x_max) - i32::from(x_min))
+      .max(i32::from(y_max) - i32::from(y_min))
+      .max(1);
+    let scale = (size - 1) as f32 / map_size as f32;
+    let to_image = |x: i16, y: i16| {
+      (
+        ((f32::from(x) - f32::from(x_min)) * scale).round(),
+        ((f32::from(y_max) - f32::from(y)) * scale).round(),
+      )
+    };
+    let (width, height) = to_image(x_max, y_min);
+    let mut image = Image::new(
+      width as usize + 1,
+      height as usize + 1,
+      Some(BACKGROUND_COLOR),
+    );
+    for line_def in map.line_defs() {
+      let vertexes = map.vertexes();
+      let start = vertexes.get(usize::from(line_def.start_vertex));
+      let end = vertexes.get(usize::from(line_def.end_vertex));
+      if let (Some(start), Some(end)) = (start, end) {
+        let color = Image::line_color(map, line_def);
+        image.draw_line(to_image(start.x, start.y), to_image(end.x, end.y), color);
+      }
+    }
+    image
+  }
+
+  fn line_color(map: &Map, line_def: &LineDef) -> u8 {
+    let sector = |side_def: u16| {
+      map
+        .side_defs()
+        .get(usize::from(side_def))
+        .and_then(|side_def| map.sectors().get(usize::from(side_def.sector)))
+    };
+    if line_def.back_side_def == NO_SIDE_DEF {
+      return WALL_COLOR;
+    }
+    match (
+      sector(line_def.front_side_def),
+      sector(line_def.back_side_def),
+    ) {
+      (Some(front), Some(back)) if front.floor_height != back.floor_height => FLOOR_CHANGE_COLOR,
+      (Some(front), Some(back)) if front.ceiling_height != back.ceiling_height => {
+        CEILING_CHANGE_COLOR
+      }
+      (Some(_), Some(_)) => TWO_SIDED_COLOR,
+      _ => WALL_COLOR,
+    }
+  }
+
+  fn set(&mut self, x: usize, y: usize, color: u8) {
+    if x < self.width && y < self.height {
+      self.pixels[y * self.width + x] = Some(color);
+    }
+  }
+
+  fn draw_line(&mut self, (x1, y1): (f32, f32), (x2, y2): (f32, f32), color: u8) {
+    let steps = (x2 - x1).abs().max((y2 - y1).abs()).max(1.0) as usize;
+    for step in 0..=steps {
+      let t = step as f32 / steps as f32;
+      let x = (x1 + (x2 - x1) * t).round() as usize;
+      let y = (y1 + (y2 - y1) * t).round() as usize;
+      self.set(x, y, color);
+    }
+  }
+
+  /// Encodes the image as RGBA PNG with one of the PLAYPAL palettes.
+  pub fn to_png(&self, palette: &[u8]) -> Result<Vec<u8>> {
+    let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+    for pixel in self.pixels.iter() {
+      match pixel {
+        Some(pixel) => {
+          let color = usize::from(*pixel) * 3;
+          rgba.extend_from_slice(&palette[color..color + 3]);
+          rgba.push(255);
+        }
+        None => rgba.extend_from_slice(&[0, 0, 0, 0]),
+      }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = Encoder::new(&mut png, self.width as u32, self.height as u32);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&rgba)?;
+    writer.finish()?;
+    Ok(png)
+  }
+}
+
+/// Exports the graphics of a wad as PNGs with its normal palette. Works
+/// without a browser, e.g. to dump all assets in CI.
+pub struct Exporter<'a> {
+  wad: &'a Wad,
+  play_pal: PlayPal,
+  textures: Textures,
+  flats: Flats,
+}
+
+impl<'a> Exporter<'a> {
+  /// Composes all textures and loads all flats once up front.
+  pub fn new(wad: &'a Wad) -> Result<Self> {
+    let play_pal = match wad.find_lump(&PlayPal::lump_name()) {
+      Some(_) => PlayPal::new(wad.read_lump(&PlayPal::lump_name())?)?,
+      None => PlayPal::default(),
+    };
+    Ok(Exporter {
+      wad,
+      play_pal,
+      textures: Textures::new(wad)?,
+      flats: Flats::new(wad)?,
+    })
+  }
+
+  /// Any lump in the picture format, like sprites, patches or menu graphics.
+  pub fn picture(&self, name: &str) -> Result<Vec<u8>> {
+    Image::from_picture(&self.wad.read_picture(name)?).to_png(self.play_pal.palette(0)?)
+  }
+
+  pub fn texture(&self, name: &str) -> Result<Vec<u8>> {
+    let texture = self.textures.get(name).ok_or_else(|| missing(name))?;
+    Image::from_texture(texture).to_png(self.play_pal.palette(0)?)
+  }
+
+  pub fn flat(&self, name: &str) -> Result<Vec<u8>> {
+    let flat = self.flats.get(name).ok_or_else(|| missing(name))?;
+    Image::from_flat(flat).to_png(self.play_pal.palette(0)?)
+  }
+
+  /// The whole map from above, `size` pixels along its longer side.
+  pub fn map(&self, name: &str, size: usize) -> Result<Vec<u8>> {
+    let map = Map::read(self.wad, name)?;
+    Image::from_map(&map, size).to_png(self.play_pal.palette(0)?)
+  }
+}
+
+fn missing(name: &str) -> DoomError {
+  DoomError::LumpMissing {
+    map: None,
+    lump: String::from(name),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::export::{Exporter, Image, FLOOR_CHANGE_COLOR, TWO_SIDED_COLOR, WALL_COLOR};
+  use crate::map::Map;
+  use crate::render::texture::Texture;
+  use crate::wad::linedef::{LineDef, NO_SIDE_DEF};
+  use crate::wad::picture::tests::picture_lump;
+  use crate::wad::picture::Picture;
+  use crate::wad::playpal::PlayPal;
+  use crate::wad::sector::Sector;
+  use crate::wad::sidedef::SideDef;
+  use crate::wad::tests::build_wad;
+  use crate::wad::vertex::Vertex;
+  use crate::wad::Wad;
+
+  fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+    let decoder = png::Decoder::new(png);
+    let mut reader = decoder.read_info().unwrap();
+    let mut rgba = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut rgba).unwrap();
+    rgba.truncate(info.buffer_size());
+    (info.width, info.height, rgba)
+  }
+
+  fn line_def(start_vertex: u16, end_vertex: u16, back_side_def: u16) -> LineDef {
+    LineDef {
+      start_vertex,
+      end_vertex,
+      flags: 0,
+      line_type: 0,
+      sector_tag: 0,
+      front_side_def: 0,
+      back_side_def,
+    }
+  }
+
+  fn side_def(sector: u16) -> SideDef {
+    SideDef {
+      x_offset: 0,
+      y_offset: 0,
+      upper_texture: String::from("-"),
+      lower_texture: String::from("-"),
+      middle_texture: String::from("-"),
+      sector,
+    }
+  }
+
+  fn sector(floor_height: i16) -> Sector {
+    Sector {
+      floor_height,
+      ceiling_height: 128,
+      floor_texture: String::from("FLOOR"),
+      ceiling_texture: String::from("CEILING"),
+      light_level: 160,
+      special_type: 0,
+      tag: 0,
+    }
+  }
+
+  #[test]
+  fn convert_pictures_and_textures() {
+    let picture = Picture::new(&picture_lump(3, &[&[(1, &[5, 6, 7])], &[]])).unwrap();
+    assert_eq!(
+      Image::from_picture(&picture).pixels,
+      vec![None, None, Some(5), None, Some(6), None]
+    );
+
+    let mut texture = Texture::new(1, 3, vec![1, 2, 3]);
+    texture.opaque[0] = vec![(1, 2)];
+    assert_eq!(
+      Image::from_texture(&texture).pixels,
+      vec![None, Some(2), None]
+    );
+  }
+
+  /// A 100x50 room, split in the middle by a line to a sector with the
+  /// given floor height.
+  fn room(floor_height: i16) -> Map {
+    Map::new(
+      vec![
+        Vertex { x: 0, y: 0 },
+        Vertex { x: 0, y: 50 },
+        Vertex { x: 100, y: 50 },
+        Vertex { x: 100, y: 0 },
+        Vertex { x: 50, y: 0 },
+        Vertex { x: 50, y: 50 },
+      ],
+      vec![
+        line_def(0, 1, NO_SIDE_DEF),
+        line_def(1, 2, NO_SIDE_DEF),
+        line_def(4, 5, 1),
+      ],
+      vec![side_def(0), side_def(1)],
+      vec![sector(0), sector(floor_height)],
+    )
+  }
+
+  #[test]
+  fn draw_maps_from_above() {
+    let map = room(24);
+    assert_eq!(map.bounds(), (0, 0, 100, 50));
+    let image = Image::from_map(&map, 11);
+    assert_eq!((image.width, image.height), (11, 6));
+    let pixel = |x: usize, y: usize| image.pixels[y * image.width + x];
+    assert_eq!(pixel(0, 3), Some(WALL_COLOR));
+    assert_eq!(pixel(7, 0), Some(WALL_COLOR));
+    assert_eq!(pixel(5, 3), Some(FLOOR_CHANGE_COLOR));
+    assert_eq!(pixel(8, 3), Some(0));
+    assert_eq!(pixel(10, 5), Some(0));
+
+    let image = Image::from_map(&room(0), 11);
+    assert_eq!(image.pixels[3 * image.width + 5], Some(TWO_SIDED_COLOR));
+  }
+
+  #[test]
+  fn encode_with_palette() {
+    let mut palette = PlayPal::default().palette(0).unwrap().to_vec();
+    palette[3..6].copy_from_slice(&[10, 20, 30]);
+    let image = Image {
+      width: 2,
+      height: 1,
+      pixels: vec![Some(1), None],
+    };
+    assert_eq!(
+      decode(&image.to_png(&palette).unwrap()),
+      (2, 1, vec![10, 20, 30, 255, 0, 0, 0, 0])
+    );
+  }
+
+  #[test]
+  fn export_from_wad() {
+    let flat = vec![7; 64 * 64];
+    let title = picture_lump(2, &[&[(0, &[1, 2])]]);
+    let mut vertexes = Vec::new();
+    for (x, y) in &[(0i16, 0i16), (0, 64), (64, 64)] {
+      vertexes.extend_from_slice(&x.to_le_bytes());
+      vertexes.extend_from_slice(&y.to_le_bytes());
+    }
+    let line_defs = [0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF];
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("TITLEPIC", &title),
+        ("E1M1", &[]),
+        ("THINGS", &[]),
+        ("LINEDEFS", &line_defs),
+        ("SIDEDEFS", &[]),
+        ("VERTEXES", &vertexes),
+        ("SEGS", &[]),
+        ("SSECTORS", &[]),
+        ("NODES", &[]),
+        ("SECTORS", &[]),
+        ("F_START", &[]),
+        ("FLOOR", &flat),
+        ("F_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let exporter = Exporter::new(&wad).unwrap();
+
+    let (width, height, rgba) = decode(&exporter.picture("TITLEPIC").unwrap());
+    assert_eq!((width, height), (1, 2));
+    // Without a PLAYPAL every index is drawn in its shade of grey.
+    assert_eq!(&rgba[4..8], &[2, 2, 2, 255]);
+    let (width, height, rgba) = decode(&exporter.flat("floor").unwrap());
+    assert_eq!((width, height), (64, 64));
+    assert_eq!(&rgba[..4], &[7, 7, 7, 255]);
+    let (width, height, rgba) = decode(&exporter.map("E1M1", 33).unwrap());
+    assert_eq!((width, height), (33, 33));
+    assert_eq!(&rgba[..4], &[WALL_COLOR, WALL_COLOR, WALL_COLOR, 255]);
+
+    assert!(matches!(
+      exporter.texture("STARTAN3"),
+      Err(DoomError::LumpMissing { .. })
+    ));
+    assert!(matches!(
+      exporter.map("E1M2", 33),
+      Err(DoomError::MapNotFound(_))
+    ));
+  }
+}
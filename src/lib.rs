@@ -1,25 +1,38 @@
 mod angle;
 mod component;
-mod datatypes;
+pub mod datatypes;
 mod entity;
-mod errors;
-mod map;
+pub mod errors;
+pub mod export;
+pub mod map;
+pub mod render;
 mod resource;
 mod system;
 mod utils;
-mod wad;
+mod validation;
+pub mod wad;
 
 use crate::component::*;
-use crate::entity::create_player;
+use crate::entity::{create_player, create_things};
+use crate::render::flat::Flats;
+use crate::render::frame_buffer::FrameBuffer;
+use crate::render::lighting::Lighting;
+use crate::render::sprite::Sprites;
+use crate::render::texture::Textures;
 use crate::resource::create_map;
+use crate::system::animation::Animation;
 use crate::system::keyboard::Keyboard;
 use crate::system::physics::Physics;
 use crate::system::view::View;
 use crate::utils::{set_panic_hook, to_vec_u8};
+use crate::validation::{Diagnostic, ValidationMode};
+use crate::wad::colormap::ColorMap;
+use crate::wad::playpal::PlayPal;
+use crate::wad::vertex::VisibleVertexes;
 use crate::wad::Wad;
 use specs::prelude::*;
 use wasm_bindgen::prelude::*;
-use crate::wad::vertex::VisibleVertexes;
+use wasm_bindgen::Clamped;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
@@ -55,14 +68,44 @@ impl Doom {
     set_panic_hook();
     let buffer = to_vec_u8(downloaded_wad);
     let wad = Wad::new(&buffer).map_err(|e| e.to_string())?;
+    let resources = WadResources::new(&wad).map_err(|e| e.to_string())?;
     let mut ecs = World::new();
     ecs.register::<KeyboardControlled>();
     ecs.register::<Position>();
     ecs.register::<Velocity>();
     ecs.register::<Rotation>();
-    Ok(Doom { wad, ecs })
+    ecs.register::<Sprite>();
+    ecs.insert(FrameBuffer::default());
+    ecs.insert(Lighting::default());
+    ecs.insert(PlayPal::default());
+    ecs.insert(Vec::<Diagnostic>::new());
+    let mut doom = Doom { wad, ecs };
+    doom.insert_resources(resources);
+    Ok(doom)
+  }
+
+  /// The PWAD is only added once all resources could be read with it, a
+  /// broken one leaves everything as it was.
+  #[wasm_bindgen(js_name = "addPwad")]
+  pub fn add_pwad(&mut self, downloaded_wad: &JsValue) -> Result<(), JsValue> {
+    let buffer = to_vec_u8(downloaded_wad);
+    let mut wad = self.wad.clone();
+    wad.add_pwad(&buffer).map_err(|e| e.to_string())?;
+    let resources = WadResources::new(&wad).map_err(|e| e.to_string())?;
+    self.wad = wad;
+    self.insert_resources(resources);
+    Ok(())
+  }
+
+  #[wasm_bindgen(js_name = "setInvulnerable")]
+  pub fn set_invulnerable(&mut self, invulnerable: bool) {
+    self
+      .ecs
+      .write_resource::<Lighting>()
+      .set_invulnerable(invulnerable);
   }
 
+  /// Runs one tic, the caller is expected to do this 35 times a second.
   pub fn tick(&mut self, events: &str) {
     match events {
       "a" => *self.ecs.write_resource() = Some(MovementCommand::Move(Direction::Left)),
@@ -93,21 +136,59 @@ impl Doom {
     Ok(JsValue::from_serde(&visible_vertexes_resource).unwrap())
   }
 
+  /// Diagnostics of the last loaded map, also when strict mode rejected it.
+  /// Empty before any map has been loaded.
+  #[wasm_bindgen(js_name = "getDiagnostics")]
+  pub fn get_diagnostics(&self) -> Result<JsValue, JsValue> {
+    let diagnostics = &*self.ecs.read_resource::<Vec<Diagnostic>>();
+    Ok(JsValue::from_serde(&diagnostics).unwrap())
+  }
+
+  /// Sprite lumps and sprites left out while loading the wads.
+  #[wasm_bindgen(js_name = "getSpriteDiagnostics")]
+  pub fn get_sprite_diagnostics(&self) -> Result<JsValue, JsValue> {
+    let sprites = &*self.ecs.read_resource::<Sprites>();
+    Ok(JsValue::from_serde(sprites.diagnostics()).unwrap())
+  }
+
+  #[wasm_bindgen(js_name = "getFrameBuffer")]
+  pub fn get_frame_buffer(&self) -> Vec<u8> {
+    self.ecs.read_resource::<FrameBuffer>().pixels.clone()
+  }
+
+  /// The frame buffer converted with one of the 14 PLAYPAL palettes, e.g. a
+  /// red one while taking damage.
+  #[wasm_bindgen(js_name = "getFrameBufferRgba")]
+  pub fn get_frame_buffer_rgba(&self, palette: usize) -> Result<Clamped<Vec<u8>>, JsValue> {
+    let play_pal = self.ecs.read_resource::<PlayPal>();
+    let palette = play_pal.palette(palette).map_err(|e| e.to_string())?;
+    Ok(Clamped(
+      self.ecs.read_resource::<FrameBuffer>().to_rgba(palette),
+    ))
+  }
+
   fn run_systems(&mut self) {
     let mut keyboard = Keyboard {};
     keyboard.run_now(&self.ecs);
     let mut physics = Physics {};
     physics.run_now(&self.ecs);
+    let mut animation = Animation {};
+    animation.run_now(&self.ecs);
     let mut view = View {};
     view.run_now(&self.ecs);
     self.ecs.maintain();
   }
 
   #[wasm_bindgen(js_name = "loadMap")]
-  pub fn load_map(&mut self, map: &str) -> Result<JsValue, JsValue> {
-    let map = create_map(map, &self.wad, &mut self.ecs).map_err(|e| e.to_string())?;
-    let js_value = JsValue::from_serde(&map).unwrap();
-    self.ecs.insert(map);
+  pub fn load_map(&mut self, map: &str, strict: Option<bool>) -> Result<JsValue, JsValue> {
+    let mode = match strict {
+      Some(true) => ValidationMode::Strict,
+      _ => ValidationMode::Lenient,
+    };
+    let loaded_map = create_map(map, &self.wad, &mut self.ecs, mode).map_err(|e| e.to_string())?;
+    create_things(map, &self.wad, &mut self.ecs).map_err(|e| e.to_string())?;
+    let js_value = JsValue::from_serde(&loaded_map).unwrap();
+    self.ecs.insert(loaded_map);
     Ok(js_value)
   }
 
@@ -121,3 +202,50 @@ impl Doom {
     Ok(())
   }
 }
+
+impl Doom {
+  fn insert_resources(&mut self, resources: WadResources) {
+    if let Some(color_map) = resources.color_map {
+      self.ecs.write_resource::<Lighting>().color_map = color_map;
+    }
+    if let Some(play_pal) = resources.play_pal {
+      self.ecs.insert(play_pal);
+    }
+    self.ecs.insert(resources.textures);
+    self.ecs.insert(resources.sprites);
+    self.ecs.insert(resources.flats);
+  }
+}
+
+/// Everything read from the wads once for all maps.
+struct WadResources {
+  /// Wads without a COLORMAP, like most map only PWADs on their own, are
+  /// drawn without any lighting.
+  color_map: Option<ColorMap>,
+  /// Without a PLAYPAL the frame buffer is shown in shades of grey.
+  play_pal: Option<PlayPal>,
+  /// PWADs can bring their own PNAMES and TEXTURE1.
+  textures: Textures,
+  sprites: Sprites,
+  flats: Flats,
+}
+
+impl WadResources {
+  fn new(wad: &Wad) -> datatypes::Result<Self> {
+    let color_map = match wad.find_lump(&ColorMap::lump_name()) {
+      Some(index) => Some(ColorMap::new(wad.lump(index)?)?),
+      None => None,
+    };
+    let play_pal = match wad.find_lump(&PlayPal::lump_name()) {
+      Some(index) => Some(PlayPal::new(wad.lump(index)?)?),
+      None => None,
+    };
+    Ok(WadResources {
+      color_map,
+      play_pal,
+      textures: Textures::new(wad)?,
+      sprites: Sprites::new(wad)?,
+      flats: Flats::new(wad)?,
+    })
+  }
+}
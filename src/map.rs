@@ -1,11 +1,18 @@
+use crate::datatypes::Result;
+use crate::errors::DoomError;
 use crate::wad::linedef::LineDef;
+use crate::wad::sector::Sector;
+use crate::wad::sidedef::SideDef;
 use crate::wad::vertex::Vertex;
+use crate::wad::Wad;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Map {
   vertexes: Vec<Vertex>,
   line_defs: Vec<LineDef>,
+  side_defs: Vec<SideDef>,
+  sectors: Vec<Sector>,
   x_min: i16,
   x_max: i16,
   y_min: i16,
@@ -13,10 +20,17 @@ pub struct Map {
 }
 
 impl Map {
-  pub fn new(vertexes: Vec<Vertex>, line_defs: Vec<LineDef>) -> Self {
+  pub fn new(
+    vertexes: Vec<Vertex>,
+    line_defs: Vec<LineDef>,
+    side_defs: Vec<SideDef>,
+    sectors: Vec<Sector>,
+  ) -> Self {
     let mut map = Map {
       vertexes,
       line_defs,
+      side_defs,
+      sectors,
       x_min: std::i16::MAX,
       x_max: std::i16::MIN,
       y_min: std::i16::MAX,
@@ -26,19 +40,50 @@ impl Map {
     map
   }
 
+  /// Reads a map without validating it, e.g. to draw it from above.
+  pub fn read(wad: &Wad, map: &str) -> Result<Self> {
+    match wad.find_map_index(map) {
+      Some(map_index) => Map::read_at(wad, map_index),
+      None => Err(DoomError::MapNotFound(String::from(map))),
+    }
+  }
+
+  pub(crate) fn read_at(wad: &Wad, map_index: usize) -> Result<Self> {
+    Ok(Map::new(
+      wad.read_wad_for(map_index)?,
+      wad.read_wad_for(map_index)?,
+      wad.read_wad_for(map_index)?,
+      wad.read_wad_for(map_index)?,
+    ))
+  }
+
+  pub fn vertexes(&self) -> &[Vertex] {
+    &self.vertexes
+  }
+
+  pub fn line_defs(&self) -> &[LineDef] {
+    &self.line_defs
+  }
+
+  pub fn side_defs(&self) -> &[SideDef] {
+    &self.side_defs
+  }
+
+  pub fn sectors(&self) -> &[Sector] {
+    &self.sectors
+  }
+
+  /// Returns `(x_min, y_min, x_max, y_max)` of all vertexes.
+  pub fn bounds(&self) -> (i16, i16, i16, i16) {
+    (self.x_min, self.y_min, self.x_max, self.y_max)
+  }
+
   fn calc_map_shift(&mut self) {
     for vertex in &self.vertexes {
-      if self.x_min > vertex.x {
-        self.x_min = vertex.x;
-      } else if self.x_max < vertex.x {
-        self.x_max = vertex.x;
-      }
-
-      if self.y_min > vertex.y {
-        self.y_min = vertex.y;
-      } else if self.y_max < vertex.y {
-        self.y_max = vertex.y;
-      }
+      self.x_min = self.x_min.min(vertex.x);
+      self.x_max = self.x_max.max(vertex.x);
+      self.y_min = self.y_min.min(vertex.y);
+      self.y_max = self.y_max.max(vertex.y);
     }
   }
 }
@@ -0,0 +1,665 @@
+pub mod camera;
+pub mod flat;
+pub mod frame_buffer;
+pub mod lighting;
+pub mod sprite;
+pub mod texture;
+pub mod visplane;
+
+use crate::render::camera::Camera;
+use crate::render::flat::{Flats, FLAT_SIZE, SKY_FLAT};
+use crate::render::frame_buffer::{FrameBuffer, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::render::lighting::Lighting;
+use crate::render::sprite::{rotation, Sprites};
+use crate::render::texture::{sample, Sky, Texture, Textures, NO_TEXTURE};
+use crate::render::visplane::{Visplane, Visplanes};
+use crate::wad::linedef::{LineDef, LOWER_UNPEGGED, NO_SIDE_DEF, UPPER_UNPEGGED};
+use crate::wad::picture::Picture;
+use crate::wad::sector::Sector;
+use crate::wad::seg::Seg;
+use crate::wad::sidedef::SideDef;
+use crate::wad::vertex::Vertex;
+use std::cmp::Ordering;
+
+/// The screen columns `x1..x2` a seg covers after FOV clipping.
+#[derive(Debug, Clone, Copy)]
+pub struct VisibleSeg {
+  pub seg: usize,
+  pub x1: usize,
+  pub x2: usize,
+}
+
+/// Textures which aren't loaded are drawn in a color derived from their name.
+pub fn placeholder_color(name: &str) -> u8 {
+  let hash = name.bytes().fold(0x811c_9dc5u32, |hash, byte| {
+    (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+  });
+  (hash % 256) as u8
+}
+
+/// A seg blocks the view if it has no back side, or if the back sector is
+/// closed like a shut door.
+pub fn is_solid(front: &Sector, back: Option<&Sector>) -> bool {
+  match back {
+    None => true,
+    Some(back) => {
+      back.ceiling_height <= front.floor_height || back.floor_height >= front.ceiling_height
+    }
+  }
+}
+
+/// The rows of every column which are still free to draw into. Everything
+/// above `top` and below `bottom` is covered by nearer walls or flats.
+pub struct ColumnClips {
+  pub top: Vec<f32>,
+  pub bottom: Vec<f32>,
+}
+
+impl ColumnClips {
+  pub fn new(width: usize, height: usize) -> Self {
+    ColumnClips {
+      top: vec![0.0; width],
+      bottom: vec![height as f32; width],
+    }
+  }
+}
+
+pub struct Renderer<'a> {
+  pub camera: Camera,
+  pub segs: &'a [Seg],
+  pub line_defs: &'a [LineDef],
+  pub side_defs: &'a [SideDef],
+  pub sectors: &'a [Sector],
+  pub vertexes: &'a [Vertex],
+  pub textures: &'a Textures,
+  pub sky: Sky,
+  pub flats: &'a Flats,
+  pub lighting: &'a Lighting,
+  pub sprites: &'a Sprites,
+  pub frame_buffer: &'a mut FrameBuffer,
+  pub clips: ColumnClips,
+  pub visplanes: Visplanes,
+  pub draw_segs: Vec<DrawSeg<'a>>,
+}
+
+impl<'a> Renderer<'a> {
+  /// Returns the sidedef facing the seg and the one on the other side.
+  fn sides(&self, seg: &Seg) -> (&'a SideDef, Option<&'a SideDef>) {
+    let (front, back) = seg.side_defs(self.line_defs);
+    let back = match back {
+      NO_SIDE_DEF => None,
+      back => Some(&self.side_defs[back as usize]),
+    };
+    (&self.side_defs[front as usize], back)
+  }
+
+  /// Segs are collected front to back and already clipped against each
+  /// other horizontally, the column clips take care of the rest. Floors and
+  /// ceilings are only collected into visplanes, see `draw_planes`.
+  pub fn draw_walls(&mut self, visible_segs: &[VisibleSeg]) {
+    for visible_seg in visible_segs {
+      self.draw_seg(visible_seg);
+    }
+  }
+
+  /// The size of a map unit at the given depth on a 320 pixel wide screen,
+  /// which is what the light diminishing is tuned for.
+  fn light_scale(&self, depth: f32) -> f32 {
+    self.camera.projection() * SCREEN_WIDTH as f32 / self.camera.width as f32 / depth
+  }
+
+  /// Fills the visplanes collected while drawing the walls.
+  pub fn draw_planes(&mut self) {
+    let planes = std::mem::take(&mut self.visplanes.planes);
+    for plane in &planes {
+      if plane.texture == SKY_FLAT {
+        self.draw_sky(plane);
+      } else {
+        self.draw_flat(plane);
+      }
+    }
+  }
+
+  /// Draws a plane as horizontal spans. All pixels of a row have the same
+  /// distance to the player.
+  fn draw_flat(&mut self, plane: &Visplane) {
+    let camera = self.camera;
+    let lighting = self.lighting;
+    let flat = self.flats.get(&plane.texture);
+    let color = placeholder_color(&plane.texture);
+    let covers = |x: usize, y: usize| match plane.rows[x] {
+      Some((top, bottom)) => top <= y && y < bottom,
+      None => false,
+    };
+    let rows = plane.rows[plane.min_x..plane.max_x].iter().flatten();
+    let top = rows.clone().map(|&(top, _)| top).min().unwrap_or(0);
+    let bottom = rows.map(|&(_, bottom)| bottom).max().unwrap_or(0);
+
+    for y in top..bottom.min(camera.height) {
+      let distance = y as f32 + 0.5 - camera.height as f32 / 2.0;
+      let depth = (camera.z - f32::from(plane.height)) * camera.projection() / distance;
+      if depth <= 0.0 {
+        continue;
+      }
+      let colormap = lighting.colormap(plane.light_level, self.light_scale(depth));
+      let texel = |x: usize| match flat {
+        Some(flat) => {
+          let (map_x, map_y) = camera.to_map_space(depth, camera.column_slope(x) * depth);
+          // The rows of a flat run from north to south.
+          let u = (map_x.floor() as i64).rem_euclid(FLAT_SIZE as i64) as usize;
+          let v = ((-map_y).floor() as i64).rem_euclid(FLAT_SIZE as i64) as usize;
+          colormap[usize::from(flat[v * FLAT_SIZE + u])]
+        }
+        None => colormap[usize::from(color)],
+      };
+
+      let mut x = plane.min_x;
+      while x < plane.max_x {
+        let start = x;
+        while x < plane.max_x && covers(x, y) {
+          x += 1;
+        }
+        if start < x {
+          self.frame_buffer.draw_span(y, start, x, texel);
+        } else {
+          x += 1;
+        }
+      }
+    }
+  }
+
+  /// The sky is mapped onto the view angle, so that it pans when the player
+  /// turns but never moves. It wraps four times around the player.
+  fn draw_sky(&mut self, plane: &Visplane) {
+    let sky = self.textures.get(self.sky.texture);
+    let colormap = self.lighting.sky_colormap();
+    let step = SCREEN_HEIGHT as f32 / self.camera.height as f32;
+    for x in plane.min_x..plane.max_x {
+      if let Some((top, bottom)) = plane.rows[x] {
+        let (top, bottom) = (top as f32, bottom as f32);
+        match sky {
+          Some(sky) => {
+            let angle = self.camera.angle + self.camera.column_slope(x).atan().to_degrees();
+            let column = sky.column(angle.rem_euclid(360.0) * 1024.0 / 360.0);
+            self.frame_buffer.draw_column_with(x, top, bottom, |y| {
+              colormap[usize::from(sample(column, y as f32 * step))]
+            });
+          }
+          None => self.frame_buffer.draw_column(
+            x,
+            top,
+            bottom,
+            colormap[usize::from(placeholder_color(self.sky.texture))],
+          ),
+        }
+      }
+    }
+  }
+
+  /// The height of a texture, or zero if it isn't loaded.
+  fn texture_height(&self, name: &str) -> f32 {
+    self
+      .textures
+      .get(name)
+      .map_or(0.0, |texture| texture.height as f32)
+  }
+
+  /// Draws the rows `top..bottom` of a wall column.
+  fn draw_wall(&mut self, x: usize, top: f32, bottom: f32, wall: Wall, depth: f32) {
+    if wall.texture == NO_TEXTURE {
+      return;
+    }
+    let colormap = self
+      .lighting
+      .colormap(wall.light_level, self.light_scale(depth));
+    match self.textures.get(wall.texture) {
+      Some(texture) => {
+        let step = depth / self.camera.projection();
+        let center = self.camera.height as f32 / 2.0 - 0.5;
+        let v0 = wall.texture_top - self.camera.z - center * step;
+        let column = texture.column(wall.u);
+        self.frame_buffer.draw_column_with(x, top, bottom, |y| {
+          colormap[usize::from(sample(column, v0 + y as f32 * step))]
+        });
+      }
+      None => self.frame_buffer.draw_column(
+        x,
+        top,
+        bottom,
+        colormap[usize::from(placeholder_color(wall.texture))],
+      ),
+    }
+  }
+
+  fn draw_seg(&mut self, visible_seg: &VisibleSeg) {
+    let seg = &self.segs[visible_seg.seg];
+    let line_def = &self.line_defs[seg.linedef as usize];
+    let (front_side, back_side) = self.sides(seg);
+    let front = &self.sectors[front_side.sector as usize];
+    let back = back_side.map(|side| &self.sectors[side.sector as usize]);
+    let solid = is_solid(front, back);
+
+    let v1 = &self.vertexes[seg.start_vertex as usize];
+    let v2 = &self.vertexes[seg.end_vertex as usize];
+    let (forward1, left1) = self.camera.to_view_space(f32::from(v1.x), f32::from(v1.y));
+    let (forward2, left2) = self.camera.to_view_space(f32::from(v2.x), f32::from(v2.y));
+    let length = (f32::from(v2.x) - f32::from(v1.x)).hypot(f32::from(v2.y) - f32::from(v1.y));
+    let offset = f32::from(seg.offset) + f32::from(front_side.x_offset);
+
+    // Walls along the axes are shaded a bit differently to add contrast.
+    let light_level = if v1.y == v2.y {
+      front.light_level.saturating_sub(16)
+    } else if v1.x == v2.x {
+      front.light_level.saturating_add(16)
+    } else {
+      front.light_level
+    };
+
+    // Where the first row of each texture is drawn, see `Wall`.
+    let front_ceiling = f32::from(front.ceiling_height);
+    let front_floor = f32::from(front.floor_height);
+    let y_offset = f32::from(front_side.y_offset);
+    let unpegged = |flag: u16| line_def.flags & flag != 0;
+    let middle_top = if unpegged(LOWER_UNPEGGED) {
+      front_floor + self.texture_height(&front_side.middle_texture)
+    } else {
+      front_ceiling
+    } + y_offset;
+    let (upper_top, lower_top) = match back {
+      Some(back) => {
+        let upper_top = if unpegged(UPPER_UNPEGGED) {
+          front_ceiling
+        } else {
+          f32::from(back.ceiling_height) + self.texture_height(&front_side.upper_texture)
+        };
+        let lower_top = if unpegged(LOWER_UNPEGGED) {
+          front_ceiling
+        } else {
+          f32::from(back.floor_height)
+        };
+        (upper_top + y_offset, lower_top + y_offset)
+      }
+      None => (middle_top, middle_top),
+    };
+
+    // Middle textures of two sided lines are see through, like grates and
+    // fences. They are drawn once and don't repeat vertically.
+    let textures: &'a Textures = self.textures;
+    let masked = match (back, textures.get(&front_side.middle_texture)) {
+      (Some(back), Some(texture)) if !solid && front_side.middle_texture != NO_TEXTURE => {
+        let texture_top = if unpegged(LOWER_UNPEGGED) {
+          front.floor_height.max(back.floor_height) as f32 + texture.height as f32
+        } else {
+          f32::from(front.ceiling_height.min(back.ceiling_height))
+        } + y_offset;
+        Some(MaskedSeg {
+          texture,
+          texture_top,
+          light_level,
+          us: Vec::with_capacity(visible_seg.x2 - visible_seg.x1),
+          drawn: vec![false; visible_seg.x2 - visible_seg.x1],
+        })
+      }
+      _ => None,
+    };
+
+    // Both ceilings show the sky, so there is no wall in between.
+    let sky = |sector: &Sector| sector.ceiling_texture == SKY_FLAT;
+    let ceiling_height = match back {
+      Some(back) if sky(front) && sky(back) => back.ceiling_height,
+      _ => front.ceiling_height,
+    };
+
+    // Ceilings above and floors below the eyes can't be seen. If the plane
+    // continues behind a two sided line, the back sector marks it instead.
+    let portal = if solid { None } else { back };
+    let mark_ceiling = (f32::from(ceiling_height) > self.camera.z || sky(front))
+      && !portal.is_some_and(|back| {
+        back.ceiling_height == ceiling_height
+          && back.ceiling_texture == front.ceiling_texture
+          && back.light_level == front.light_level
+      });
+    let mark_floor = front_floor < self.camera.z
+      && !portal.is_some_and(|back| {
+        back.floor_height == front.floor_height
+          && back.floor_texture == front.floor_texture
+          && back.light_level == front.light_level
+      });
+    let (x1, x2) = (visible_seg.x1, visible_seg.x2);
+    let ceiling_plane = if mark_ceiling {
+      let plane = self.visplanes.find(
+        front.ceiling_height,
+        &front.ceiling_texture,
+        front.light_level,
+        x1,
+        x2,
+      );
+      Some(plane)
+    } else {
+      None
+    };
+    let floor_plane = if mark_floor {
+      let plane = self.visplanes.find(
+        front.floor_height,
+        &front.floor_texture,
+        front.light_level,
+        x1,
+        x2,
+      );
+      Some(plane)
+    } else {
+      None
+    };
+
+    let mut draw_seg = DrawSeg {
+      x1,
+      depths: Vec::with_capacity(x2 - x1),
+      tops: Vec::with_capacity(x2 - x1),
+      bottoms: Vec::with_capacity(x2 - x1),
+      masked,
+    };
+    for x in x1..x2 {
+      // Intersect the ray through the column with the seg in camera space.
+      let slope = self.camera.column_slope(x);
+      let denominator = (left2 - left1) - slope * (forward2 - forward1);
+      if denominator.abs() < f32::EPSILON {
+        draw_seg.push(f32::INFINITY, 0.0, &self.clips, x);
+        continue;
+      }
+      let t = ((slope * forward1 - left1) / denominator).clamp(0.0, 1.0);
+      let depth = (forward1 + t * (forward2 - forward1)).max(1.0);
+      let u = offset + t * length;
+
+      let top = self.clips.top[x];
+      let bottom = self.clips.bottom[x];
+      let clip = |y: f32| y.max(top).min(bottom);
+      let ceiling = clip(self.camera.to_screen_y(f32::from(ceiling_height), depth));
+      let floor = clip(self.camera.to_screen_y(front_floor, depth));
+      if let Some(plane) = ceiling_plane {
+        self.visplanes.mark(plane, x, top, ceiling);
+      }
+      if let Some(plane) = floor_plane {
+        self.visplanes.mark(plane, x, floor, bottom);
+      }
+
+      let upper = Wall {
+        texture: &front_side.upper_texture,
+        texture_top: upper_top,
+        u,
+        light_level,
+      };
+      let lower = Wall {
+        texture: &front_side.lower_texture,
+        texture_top: lower_top,
+        u,
+        light_level,
+      };
+      match portal {
+        Some(back) => {
+          if back.ceiling_height < ceiling_height {
+            let upper_bottom = clip(
+              self
+                .camera
+                .to_screen_y(f32::from(back.ceiling_height), depth),
+            );
+            self.draw_wall(x, ceiling, upper_bottom, upper, depth);
+            self.clips.top[x] = upper_bottom;
+          } else if mark_ceiling {
+            self.clips.top[x] = ceiling;
+          }
+          if back.floor_height > front.floor_height {
+            let lower_top = clip(self.camera.to_screen_y(f32::from(back.floor_height), depth));
+            self.draw_wall(x, lower_top, floor, lower, depth);
+            self.clips.bottom[x] = lower_top;
+          } else if mark_floor {
+            self.clips.bottom[x] = floor;
+          }
+        }
+        None => {
+          let wall = match back {
+            None => Wall {
+              texture: &front_side.middle_texture,
+              texture_top: middle_top,
+              u,
+              light_level,
+            },
+            // Closed doors are covered by their upper or lower texture.
+            Some(back) if back.ceiling_height <= front.floor_height => upper,
+            Some(_) => lower,
+          };
+          self.draw_wall(x, ceiling, floor, wall, depth);
+          self.clips.top[x] = bottom;
+          self.clips.bottom[x] = top;
+        }
+      }
+      draw_seg.push(depth, u, &self.clips, x);
+    }
+    self.draw_segs.push(draw_seg);
+  }
+
+  /// Sprites and masked middle textures are drawn back to front after all
+  /// walls and flats. Before a sprite is drawn, all masked columns behind it
+  /// are, the rest is drawn over the sprites at the end.
+  pub fn draw_masked(&mut self, things: &[MapThing]) {
+    let mut vis_sprites = things
+      .iter()
+      .filter_map(|thing| self.project_sprite(thing))
+      .collect::<Vec<_>>();
+    vis_sprites.sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap_or(Ordering::Equal));
+    for vis_sprite in &vis_sprites {
+      self.draw_masked_segs(vis_sprite.start, vis_sprite.end, vis_sprite.depth);
+      self.draw_sprite(vis_sprite);
+    }
+    self.draw_masked_segs(0, self.camera.width, 0.0);
+  }
+
+  /// Draws the masked columns `x1..x2` which are farther away than `depth`,
+  /// from back to front.
+  fn draw_masked_segs(&mut self, x1: usize, x2: usize, depth: f32) {
+    for index in (0..self.draw_segs.len()).rev() {
+      let draw_seg = &self.draw_segs[index];
+      if draw_seg.masked.is_none() {
+        continue;
+      }
+      let seg_x1 = draw_seg.x1;
+      let start = x1.max(seg_x1);
+      let end = x2.min(seg_x1 + draw_seg.depths.len());
+      for x in start..end {
+        if self.draw_segs[index].depths[x - seg_x1] > depth {
+          self.draw_masked_column(index, x);
+        }
+      }
+    }
+  }
+
+  fn draw_masked_column(&mut self, index: usize, x: usize) {
+    let draw_seg = &self.draw_segs[index];
+    let i = x - draw_seg.x1;
+    let masked = match &draw_seg.masked {
+      Some(masked) if !masked.drawn[i] => masked,
+      _ => return,
+    };
+    let depth = draw_seg.depths[i];
+    if !depth.is_finite() {
+      return;
+    }
+    let (clip_top, clip_bottom) = (draw_seg.tops[i], draw_seg.bottoms[i]);
+    let colormap = self
+      .lighting
+      .colormap(masked.light_level, self.light_scale(depth));
+    let column = masked.texture.column(masked.us[i]);
+    let step = depth / self.camera.projection();
+    let top = self.camera.to_screen_y(masked.texture_top, depth);
+
+    for &(start, end) in masked.texture.opaque_rows(masked.us[i]) {
+      let post_top = top + start as f32 / step;
+      let post_bottom = top + end as f32 / step;
+      self.frame_buffer.draw_column_with(
+        x,
+        post_top.max(clip_top),
+        post_bottom.min(clip_bottom),
+        |y| {
+          let v = (((y as f32 + 0.5 - top) * step) as usize).min(end - 1);
+          colormap[usize::from(column[v])]
+        },
+      );
+    }
+    if let Some(masked) = &mut self.draw_segs[index].masked {
+      masked.drawn[i] = true;
+    }
+  }
+
+  fn project_sprite(&self, thing: &MapThing) -> Option<VisSprite<'a>> {
+    let (forward, left) = self.camera.to_view_space(thing.x, thing.y);
+    if forward < MIN_SPRITE_DEPTH {
+      return None;
+    }
+    let view_angle = (thing.y - self.camera.y)
+      .atan2(thing.x - self.camera.x)
+      .to_degrees();
+    let sprites: &'a Sprites = self.sprites;
+    let (picture, flip) =
+      sprites.frame(thing.sprite, thing.frame, rotation(view_angle, thing.angle))?;
+
+    let scale = self.camera.projection() / forward;
+    let x1 = self.camera.width as f32 / 2.0 - (left + f32::from(picture.left_offset)) * scale;
+    let x2 = x1 + picture.width as f32 * scale;
+    let start = x1.round().max(0.0) as usize;
+    let end = (x2.round().max(0.0) as usize).min(self.camera.width);
+    if start >= end {
+      return None;
+    }
+    Some(VisSprite {
+      picture,
+      flip,
+      x1,
+      start,
+      end,
+      scale,
+      depth: forward,
+      top: thing.z + f32::from(picture.top_offset),
+      light_level: thing.light_level,
+    })
+  }
+
+  /// The rows of a column which aren't covered by a wall or flat in front
+  /// of the given depth. Draw segs are in front to back order and their
+  /// clips include all nearer ones, so the farthest one in front wins.
+  fn sprite_clip(&self, x: usize, depth: f32) -> (f32, f32) {
+    for draw_seg in self.draw_segs.iter().rev() {
+      if x < draw_seg.x1 || x >= draw_seg.x1 + draw_seg.depths.len() {
+        continue;
+      }
+      let i = x - draw_seg.x1;
+      if draw_seg.depths[i] < depth {
+        return (draw_seg.tops[i], draw_seg.bottoms[i]);
+      }
+    }
+    (0.0, self.camera.height as f32)
+  }
+
+  fn draw_sprite(&mut self, vis_sprite: &VisSprite) {
+    let picture = vis_sprite.picture;
+    let scale = vis_sprite.scale;
+    let colormap = self
+      .lighting
+      .colormap(vis_sprite.light_level, self.light_scale(vis_sprite.depth));
+    let top = self.camera.to_screen_y(vis_sprite.top, vis_sprite.depth);
+
+    for x in vis_sprite.start..vis_sprite.end {
+      let (clip_top, clip_bottom) = self.sprite_clip(x, vis_sprite.depth);
+      let u = (((x as f32 + 0.5 - vis_sprite.x1) / scale) as usize).min(picture.width - 1);
+      let u = if vis_sprite.flip {
+        picture.width - 1 - u
+      } else {
+        u
+      };
+      for post in &picture.columns[u] {
+        let post_top = top + post.top_delta as f32 * scale;
+        let post_bottom = post_top + post.pixels.len() as f32 * scale;
+        self.frame_buffer.draw_column_with(
+          x,
+          post_top.max(clip_top),
+          post_bottom.min(clip_bottom),
+          |y| {
+            let v = ((y as f32 + 0.5 - post_top) / scale) as usize;
+            colormap[usize::from(post.pixels[v.min(post.pixels.len() - 1)])]
+          },
+        );
+      }
+    }
+  }
+}
+
+/// Sprites closer than this are behind the projection plane.
+const MIN_SPRITE_DEPTH: f32 = 4.0;
+
+/// A thing on the map which is drawn as a sprite. `z` is the height of the
+/// floor it stands on.
+pub struct MapThing<'a> {
+  pub x: f32,
+  pub y: f32,
+  pub z: f32,
+  pub angle: f32,
+  pub sprite: &'a str,
+  pub frame: char,
+  pub light_level: i16,
+}
+
+/// A sprite projected onto the screen, covering the columns `start..end`.
+/// `x1` is the unclipped left edge and `top` the height of its first row.
+struct VisSprite<'a> {
+  picture: &'a Picture,
+  flip: bool,
+  x1: f32,
+  start: usize,
+  end: usize,
+  scale: f32,
+  depth: f32,
+  top: f32,
+  light_level: i16,
+}
+
+/// The depth of a drawn seg and the column clips right after it was drawn,
+/// for the columns starting at `x1`. Sprites behind it are clipped by them,
+/// as is its masked middle texture.
+pub struct DrawSeg<'a> {
+  pub x1: usize,
+  pub depths: Vec<f32>,
+  pub tops: Vec<f32>,
+  pub bottoms: Vec<f32>,
+  pub masked: Option<MaskedSeg<'a>>,
+}
+
+impl<'a> DrawSeg<'a> {
+  fn push(&mut self, depth: f32, u: f32, clips: &ColumnClips, x: usize) {
+    self.depths.push(depth);
+    self.tops.push(clips.top[x]);
+    self.bottoms.push(clips.bottom[x]);
+    if let Some(masked) = &mut self.masked {
+      masked.us.push(u);
+    }
+  }
+}
+
+/// The middle texture of a two sided line, drawn in the masked pass. `us`
+/// holds the horizontal texture coordinate of every column, `drawn` the
+/// columns which are done.
+pub struct MaskedSeg<'a> {
+  pub texture: &'a Texture,
+  pub texture_top: f32,
+  pub light_level: i16,
+  pub us: Vec<f32>,
+  pub drawn: Vec<bool>,
+}
+
+/// A wall texture placed on a seg. `texture_top` is the height at which
+/// the first texture row is drawn, which depends on the pegging of the
+/// linedef, and `u` the horizontal texture coordinate of the column.
+#[derive(Clone, Copy)]
+struct Wall<'a> {
+  texture: &'a str,
+  texture_top: f32,
+  u: f32,
+  light_level: i16,
+}
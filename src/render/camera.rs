@@ -0,0 +1,56 @@
+/// Eye height of the player above the floor.
+pub const VIEW_HEIGHT: f32 = 41.0;
+
+/// The player's view into the map. Angles are given in degrees, like
+/// everywhere else, and grow counter clockwise.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+  pub x: f32,
+  pub y: f32,
+  pub z: f32,
+  pub angle: f32,
+  pub fov: f32,
+  pub width: usize,
+  pub height: usize,
+}
+
+impl Camera {
+  /// Distance of the projection plane, so that the FOV spans the screen width.
+  pub fn projection(&self) -> f32 {
+    self.width as f32 / 2.0 / (self.fov.to_radians() / 2.0).tan()
+  }
+
+  /// Transforms a map position into (forward, left) camera space.
+  pub fn to_view_space(self, x: f32, y: f32) -> (f32, f32) {
+    let (sin, cos) = self.angle.to_radians().sin_cos();
+    let dx = x - self.x;
+    let dy = y - self.y;
+    (dx * cos + dy * sin, dy * cos - dx * sin)
+  }
+
+  /// Transforms a (forward, left) camera space position back onto the map.
+  pub fn to_map_space(self, forward: f32, left: f32) -> (f32, f32) {
+    let (sin, cos) = self.angle.to_radians().sin_cos();
+    (
+      self.x + forward * cos - left * sin,
+      self.y + forward * sin + left * cos,
+    )
+  }
+
+  /// The slope (left / forward) of the ray through the center of a column.
+  pub fn column_slope(&self, x: usize) -> f32 {
+    (self.width as f32 / 2.0 - x as f32 - 0.5) / self.projection()
+  }
+
+  /// Maps an angle relative to the view direction onto the screen. The
+  /// angle has to be inside the FOV.
+  pub fn angle_to_x(&self, angle: f32) -> usize {
+    let x = self.width as f32 / 2.0 - angle.to_radians().tan() * self.projection();
+    (x.round().max(0.0) as usize).min(self.width)
+  }
+
+  /// Projects a height at the given depth onto a screen row.
+  pub fn to_screen_y(self, z: f32, depth: f32) -> f32 {
+    self.height as f32 / 2.0 - (z - self.z) * self.projection() / depth
+  }
+}
@@ -0,0 +1,204 @@
+use crate::datatypes::Result;
+use crate::wad::animated::Animated;
+use crate::wad::sector::Sector;
+use crate::wad::Wad;
+use std::collections::HashMap;
+
+/// Flats are square images of 64 by 64 pixels, stored row by row.
+pub const FLAT_SIZE: usize = 64;
+
+/// Ceilings with this flat show the sky instead.
+pub const SKY_FLAT: &str = "F_SKY1";
+
+/// The frames of an animated flat and the number of tics each is shown.
+#[derive(Debug)]
+struct Animation {
+  frames: Vec<String>,
+  speed: usize,
+}
+
+/// All floor and ceiling flats of the loaded wads by name.
+#[derive(Debug, Default)]
+pub struct Flats {
+  flats: HashMap<String, Vec<u8>>,
+  animations: Vec<Animation>,
+  /// Every frame of a running animation points to the flat it shows now.
+  frames: HashMap<String, String>,
+  tic: usize,
+}
+
+impl Flats {
+  /// Loads all flats between the flat markers. The animations come from the
+  /// ANIMATED lump if there is one, otherwise the built in ones are used.
+  /// Animations whose first or last frame is missing are left out, like
+  /// NUKAGE in wads without it.
+  pub fn new(wad: &Wad) -> Result<Self> {
+    let mut flats = Flats::default();
+    let lumps = wad.flats()?;
+    for (name, pixels) in lumps.iter() {
+      flats.insert(name, Vec::from(*pixels));
+    }
+
+    let animations = match wad.find_lump(&Animated::lump_name()) {
+      Some(_) => Animated::read_all(wad.read_lump(&Animated::lump_name())?)
+        .map_err(|e| e.in_lump(&Animated::lump_name()))?,
+      None => Animated::flats(),
+    };
+    let names: Vec<_> = lumps.iter().map(|(name, _)| name.to_uppercase()).collect();
+    for animated in animations.iter().filter(|animated| !animated.texture) {
+      let first = names.iter().position(|name| *name == animated.first);
+      let last = names.iter().position(|name| *name == animated.last);
+      if let (Some(first), Some(last)) = (first, last) {
+        if first < last {
+          flats.animations.push(Animation {
+            frames: names[first..=last].to_vec(),
+            speed: animated.speed.max(1),
+          });
+        }
+      }
+    }
+    Ok(flats)
+  }
+
+  pub fn insert(&mut self, name: &str, pixels: Vec<u8>) {
+    self.flats.insert(name.to_uppercase(), pixels);
+  }
+
+  /// Returns the flat with the given name, if it has the size of a flat.
+  /// Animated flats return their current frame.
+  pub fn get(&self, name: &str) -> Option<&[u8]> {
+    let name = name.to_uppercase();
+    let name = self.frames.get(&name).unwrap_or(&name);
+    self
+      .flats
+      .get(name)
+      .map(Vec::as_slice)
+      .filter(|pixels| pixels.len() == FLAT_SIZE * FLAT_SIZE)
+  }
+
+  pub fn floor(&self, sector: &Sector) -> Option<&[u8]> {
+    self.get(&sector.floor_texture)
+  }
+
+  pub fn ceiling(&self, sector: &Sector) -> Option<&[u8]> {
+    self.get(&sector.ceiling_texture)
+  }
+
+  /// Advances all animations by one tic. Like in the original engine, every
+  /// frame of an animation keeps its distance to the others, so a sector
+  /// with NUKAGE2 is always one frame ahead of one with NUKAGE1.
+  pub fn tick(&mut self) {
+    self.tic += 1;
+    for animation in self.animations.iter() {
+      let count = animation.frames.len();
+      for (index, frame) in animation.frames.iter().enumerate() {
+        let shown = (self.tic / animation.speed + index) % count;
+        self
+          .frames
+          .insert(frame.clone(), animation.frames[shown].clone());
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::render::flat::{Flats, FLAT_SIZE};
+  use crate::wad::sector::Sector;
+  use crate::wad::tests::build_wad;
+  use crate::wad::Wad;
+
+  fn flat(color: u8) -> Vec<u8> {
+    vec![color; FLAT_SIZE * FLAT_SIZE]
+  }
+
+  fn color(flats: &Flats, name: &str) -> Option<u8> {
+    flats.get(name).map(|pixels| pixels[0])
+  }
+
+  #[test]
+  fn load_flats_and_look_them_up_for_sectors() {
+    let (floor, ceiling) = (flat(1), flat(2));
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("F_START", &[]),
+        ("FLOOR4_8", &floor),
+        ("CEIL3_5", &ceiling),
+        ("BROKEN", &[3; 10]),
+        ("F_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let flats = Flats::new(&wad).unwrap();
+    let sector = Sector {
+      floor_height: 0,
+      ceiling_height: 128,
+      floor_texture: String::from("floor4_8"),
+      ceiling_texture: String::from("CEIL3_5"),
+      light_level: 160,
+      special_type: 0,
+      tag: 0,
+    };
+    assert_eq!(flats.floor(&sector).map(|pixels| pixels[0]), Some(1));
+    assert_eq!(flats.ceiling(&sector).map(|pixels| pixels[0]), Some(2));
+    assert_eq!(color(&flats, "BROKEN"), None);
+  }
+
+  #[test]
+  fn animate_flats_every_eight_tics() {
+    let frames = [flat(1), flat(2), flat(3)];
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("F_START", &[]),
+        ("NUKAGE1", &frames[0]),
+        ("NUKAGE2", &frames[1]),
+        ("NUKAGE3", &frames[2]),
+        ("LAVA1", &frames[0]),
+        ("F_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let mut flats = Flats::new(&wad).unwrap();
+    assert_eq!(color(&flats, "NUKAGE1"), Some(1));
+    for _ in 0..7 {
+      flats.tick();
+    }
+    assert_eq!(color(&flats, "NUKAGE1"), Some(1));
+    assert_eq!(color(&flats, "NUKAGE3"), Some(3));
+    flats.tick();
+    assert_eq!(color(&flats, "NUKAGE1"), Some(2));
+    assert_eq!(color(&flats, "NUKAGE3"), Some(1));
+    for _ in 0..16 {
+      flats.tick();
+    }
+    assert_eq!(color(&flats, "NUKAGE1"), Some(1));
+    // LAVA4 is missing, so there is nothing to animate.
+    assert_eq!(color(&flats, "LAVA1"), Some(1));
+  }
+
+  #[test]
+  fn animate_flats_from_animated_lump() {
+    let frames = [flat(1), flat(2)];
+    let mut animated = vec![0];
+    animated.extend(b"GOOP2\0\0\0\0GOOP1\0\0\0\0");
+    animated.extend(&[2, 0, 0, 0, 0xFF]);
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("ANIMATED", &animated),
+        ("F_START", &[]),
+        ("GOOP1", &frames[0]),
+        ("GOOP2", &frames[1]),
+        ("F_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let mut flats = Flats::new(&wad).unwrap();
+    flats.tick();
+    assert_eq!(color(&flats, "GOOP1"), Some(1));
+    flats.tick();
+    assert_eq!(color(&flats, "GOOP1"), Some(2));
+  }
+}
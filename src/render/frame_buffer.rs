@@ -0,0 +1,88 @@
+pub const SCREEN_WIDTH: usize = 320;
+pub const SCREEN_HEIGHT: usize = 200;
+
+/// A palette indexed image, stored row by row.
+#[derive(Debug)]
+pub struct FrameBuffer {
+  pub width: usize,
+  pub height: usize,
+  pub pixels: Vec<u8>,
+}
+
+impl Default for FrameBuffer {
+  fn default() -> Self {
+    FrameBuffer::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+  }
+}
+
+impl FrameBuffer {
+  pub fn new(width: usize, height: usize) -> Self {
+    FrameBuffer {
+      width,
+      height,
+      pixels: vec![0; width * height],
+    }
+  }
+
+  pub fn clear(&mut self) {
+    for pixel in self.pixels.iter_mut() {
+      *pixel = 0;
+    }
+  }
+
+  /// Fills the rows from `top` up to (not including) `bottom` of a column.
+  pub fn draw_column(&mut self, x: usize, top: f32, bottom: f32, color: u8) {
+    let top = top.ceil().max(0.0) as usize;
+    let bottom = (bottom.ceil().max(0.0) as usize).min(self.height);
+    for y in top..bottom {
+      self.pixels[y * self.width + x] = color;
+    }
+  }
+
+  /// Fills the rows from `top` up to (not including) `bottom` of a column
+  /// with the texel of each row.
+  pub fn draw_column_with(&mut self, x: usize, top: f32, bottom: f32, texel: impl Fn(usize) -> u8) {
+    let top = top.ceil().max(0.0) as usize;
+    let bottom = (bottom.ceil().max(0.0) as usize).min(self.height);
+    for y in top..bottom {
+      self.pixels[y * self.width + x] = texel(y);
+    }
+  }
+
+  /// Fills the columns `x1..x2` of a row with the texel of each column.
+  pub fn draw_span(&mut self, y: usize, x1: usize, x2: usize, texel: impl Fn(usize) -> u8) {
+    for x in x1..x2 {
+      self.pixels[y * self.width + x] = texel(x);
+    }
+  }
+
+  /// Looks every pixel up in a PLAYPAL palette and returns them as RGBA,
+  /// ready to be put into an `ImageData`.
+  pub fn to_rgba(&self, palette: &[u8]) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+    for &pixel in self.pixels.iter() {
+      let color = usize::from(pixel) * 3;
+      rgba.extend_from_slice(&palette[color..color + 3]);
+      rgba.push(255);
+    }
+    rgba
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::render::frame_buffer::FrameBuffer;
+  use crate::wad::playpal::PlayPal;
+
+  #[test]
+  fn convert_to_rgba() {
+    let mut frame_buffer = FrameBuffer::new(2, 1);
+    frame_buffer.pixels = vec![1, 255];
+    let mut palette = PlayPal::default().palette(0).unwrap().to_vec();
+    palette[3..6].copy_from_slice(&[10, 20, 30]);
+    assert_eq!(
+      frame_buffer.to_rgba(&palette),
+      vec![10, 20, 30, 255, 255, 255, 255, 255]
+    );
+  }
+}
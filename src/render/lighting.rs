@@ -0,0 +1,79 @@
+use crate::wad::colormap::{ColorMap, INVULNERABILITY, LIGHT_LEVELS};
+
+/// Picks the colormap for walls and flats from the light level of their
+/// sector and their distance to the player.
+#[derive(Debug, Default)]
+pub struct Lighting {
+  pub color_map: ColorMap,
+  /// Overrides all lighting, e.g. with the invulnerability map.
+  pub fixed_colormap: Option<usize>,
+}
+
+impl Lighting {
+  pub fn new(color_map: ColorMap) -> Self {
+    Lighting {
+      color_map,
+      fixed_colormap: None,
+    }
+  }
+
+  pub fn set_invulnerable(&mut self, invulnerable: bool) {
+    self.fixed_colormap = if invulnerable {
+      Some(INVULNERABILITY)
+    } else {
+      None
+    };
+  }
+
+  /// `scale` is the size of one map unit on a 320 pixel wide screen. The
+  /// closer something gets, the brighter it is drawn, like in vanilla.
+  pub fn colormap(&self, light_level: i16, scale: f32) -> &[u8] {
+    let index = match self.fixed_colormap {
+      Some(index) => index,
+      None => {
+        let start = (15 - i32::from(light_level.clamp(0, 255) >> 4)) * 4;
+        (start - (scale * 8.0) as i32).clamp(0, LIGHT_LEVELS as i32 - 1) as usize
+      }
+    };
+    self.color_map.map(index)
+  }
+
+  /// The sky is always drawn at full brightness.
+  pub fn sky_colormap(&self) -> &[u8] {
+    self.color_map.map(self.fixed_colormap.unwrap_or(0))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::render::lighting::Lighting;
+  use crate::wad::colormap::{ColorMap, COLORMAP_SIZE};
+
+  fn lighting() -> Lighting {
+    let lump = (0..34 * COLORMAP_SIZE)
+      .map(|i| (i / COLORMAP_SIZE) as u8)
+      .collect::<Vec<_>>();
+    Lighting::new(ColorMap::new(&lump).unwrap())
+  }
+
+  #[test]
+  fn diminish_light_with_distance() {
+    let lighting = lighting();
+    assert_eq!(lighting.colormap(255, 1.0)[0], 0);
+    assert_eq!(lighting.colormap(160, 1.0)[0], 12);
+    assert_eq!(lighting.colormap(160, 0.25)[0], 18);
+    assert_eq!(lighting.colormap(160, 0.0)[0], 20);
+    assert_eq!(lighting.colormap(0, 0.0)[0], 31);
+  }
+
+  #[test]
+  fn override_lighting_while_invulnerable() {
+    let mut lighting = lighting();
+    lighting.set_invulnerable(true);
+    assert_eq!(lighting.colormap(255, 1.0)[0], 32);
+    assert_eq!(lighting.sky_colormap()[0], 32);
+    lighting.set_invulnerable(false);
+    assert_eq!(lighting.colormap(255, 1.0)[0], 0);
+    assert_eq!(lighting.sky_colormap()[0], 0);
+  }
+}
@@ -0,0 +1,392 @@
+use crate::datatypes::Result;
+use crate::errors::DoomError;
+use crate::validation::{Diagnostic, Severity};
+use crate::wad::picture::Picture;
+use crate::wad::{Namespace, Wad};
+use std::collections::HashMap;
+
+/// Frames go from `A` to `]`, like in the original engine.
+const MAX_FRAMES: usize = 29;
+/// Things are seen from 8 directions, rotation 0 stands for all of them.
+const ROTATIONS: usize = 8;
+
+/// One picture a sprite lump name stands for, e.g. `TROOA2A8` stands for
+/// frame `A` from rotation 2 and, mirrored, from rotation 8.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteLump {
+  pub frame: char,
+  pub rotation: u8,
+  pub flip: bool,
+}
+
+/// Splits a sprite lump name into the sprite name and the frames it holds.
+pub fn parse_lump_name(lump_name: &str) -> Result<(String, Vec<SpriteLump>)> {
+  let invalid = || DoomError::InvalidSprite {
+    sprite: String::from(lump_name),
+    cause: String::from("is no valid sprite lump name"),
+  };
+  let name = lump_name.to_uppercase();
+  let chars: Vec<char> = name.chars().collect();
+  if !name.is_ascii() || (chars.len() != 6 && chars.len() != 8) {
+    return Err(invalid());
+  }
+  let mut lumps = Vec::new();
+  for (index, pair) in chars[4..].chunks(2).enumerate() {
+    let frame = pair[0];
+    let rotation = pair[1].to_digit(10).ok_or_else(invalid)? as u8;
+    if frame < 'A' || frame_index(frame) >= MAX_FRAMES || usize::from(rotation) > ROTATIONS {
+      return Err(invalid());
+    }
+    lumps.push(SpriteLump {
+      frame,
+      rotation,
+      flip: index == 1,
+    });
+  }
+  if lumps.len() == 2 && (lumps[0].rotation == 0 || lumps[1].rotation == 0) {
+    return Err(invalid());
+  }
+  Ok((chars[..4].iter().collect(), lumps))
+}
+
+fn frame_index(frame: char) -> usize {
+  (frame as usize).wrapping_sub('A' as usize)
+}
+
+/// The pictures of one frame from all 8 rotations, either one for all of
+/// them or one per rotation, as index into the pictures and whether they
+/// have to be mirrored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpriteFrame {
+  pub rotate: bool,
+  pub rotations: [Option<(usize, bool)>; ROTATIONS],
+}
+
+impl SpriteFrame {
+  fn missing_rotations(&self) -> Vec<usize> {
+    (1..=ROTATIONS)
+      .filter(|&rotation| self.rotations[rotation - 1].is_none())
+      .collect()
+  }
+}
+
+/// All sprites of the loaded wads, with a frame table per sprite name.
+#[derive(Debug, Default)]
+pub struct Sprites {
+  pictures: Vec<Picture>,
+  sprites: HashMap<String, Vec<SpriteFrame>>,
+  diagnostics: Vec<Diagnostic>,
+}
+
+impl Sprites {
+  /// Reads all sprites between the sprite markers. Later lumps replace the
+  /// rotations of earlier ones, so PWADs can swap single pictures as well
+  /// as turn a frame with rotations into one without. Broken lumps and
+  /// sprites are left out and reported as warnings instead.
+  pub fn new(wad: &Wad) -> Result<Self> {
+    let mut sprites = Sprites::default();
+    for (index, directory) in wad.directories().iter().enumerate() {
+      if directory.namespace != Namespace::Sprites || directory.lump_size == 0 {
+        continue;
+      }
+      let inserted = Picture::new(wad.lump(index)?)
+        .map_err(|e| e.in_lump(&directory.lump_name))
+        .and_then(|picture| sprites.insert(&directory.lump_name, picture));
+      if let Err(e) = inserted {
+        sprites.warn(e);
+      }
+    }
+    for e in sprites.validate() {
+      if let DoomError::InvalidSprite { ref sprite, .. } = e {
+        sprites.sprites.remove(sprite);
+      }
+      sprites.warn(e);
+    }
+    Ok(sprites)
+  }
+
+  fn warn(&mut self, e: DoomError) {
+    self.diagnostics.push(Diagnostic {
+      severity: Severity::Warning,
+      message: e.to_string(),
+    });
+  }
+
+  pub fn insert(&mut self, lump_name: &str, picture: Picture) -> Result<()> {
+    let (name, lumps) = parse_lump_name(lump_name)?;
+    let index = self.pictures.len();
+    self.pictures.push(picture);
+    let frames = self.sprites.entry(name).or_default();
+    for lump in lumps {
+      let frame = frame_index(lump.frame);
+      if frames.len() <= frame {
+        frames.resize(frame + 1, SpriteFrame::default());
+      }
+      let frame = &mut frames[frame];
+      if lump.rotation == 0 {
+        *frame = SpriteFrame {
+          rotate: false,
+          rotations: [Some((index, lump.flip)); ROTATIONS],
+        };
+      } else {
+        if !frame.rotate {
+          *frame = SpriteFrame::default();
+        }
+        frame.rotate = true;
+        frame.rotations[usize::from(lump.rotation) - 1] = Some((index, lump.flip));
+      }
+    }
+    Ok(())
+  }
+
+  /// Every sprite needs all frames from `A` up to its last one, and every
+  /// frame with rotations needs all 8 of them. Returns the first problem of
+  /// every broken sprite, ordered by sprite name.
+  pub fn validate(&self) -> Vec<DoomError> {
+    let mut names: Vec<_> = self.sprites.keys().collect();
+    names.sort();
+    let mut errors = Vec::new();
+    for name in names {
+      for (index, frame) in self.sprites[name].iter().enumerate() {
+        let frame_name = (b'A' + index as u8) as char;
+        let missing = frame.missing_rotations();
+        let cause = if missing.len() == ROTATIONS {
+          format!("is missing frame {}", frame_name)
+        } else if !missing.is_empty() {
+          let missing: Vec<_> = missing.iter().map(ToString::to_string).collect();
+          format!(
+            "frame {} is missing rotations {}",
+            frame_name,
+            missing.join(", ")
+          )
+        } else {
+          continue;
+        };
+        errors.push(DoomError::InvalidSprite {
+          sprite: name.clone(),
+          cause,
+        });
+        break;
+      }
+    }
+    errors
+  }
+
+  /// Lumps and sprites left out while loading.
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self.diagnostics
+  }
+
+  pub fn frames(&self, name: &str) -> Option<&[SpriteFrame]> {
+    self.sprites.get(&name.to_uppercase()).map(Vec::as_slice)
+  }
+
+  /// Returns the picture of a frame seen from rotation 1 (the front) to 8,
+  /// and whether it has to be mirrored.
+  pub fn frame(&self, name: &str, frame: char, rotation: u8) -> Option<(&Picture, bool)> {
+    let frames = self.frames(name)?;
+    let sprite_frame = frames.get(frame_index(frame.to_ascii_uppercase()))?;
+    let rotation = usize::from(rotation).checked_sub(1)?;
+    let (picture, flip) = (*sprite_frame.rotations.get(rotation)?)?;
+    Some((&self.pictures[picture], flip))
+  }
+}
+
+/// The rotation (1 to 8) of a thing facing `angle`, as seen from a viewer in
+/// the direction `view_angle`. Rotation 1 shows the front of the thing.
+pub fn rotation(view_angle: f32, angle: f32) -> u8 {
+  ((view_angle - angle + 202.5).rem_euclid(360.0) / 45.0) as u8 % 8 + 1
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::render::sprite::{parse_lump_name, rotation, SpriteLump, Sprites};
+  use crate::wad::picture::tests::picture_lump;
+  use crate::wad::picture::Picture;
+  use crate::wad::tests::build_wad;
+  use crate::wad::Wad;
+
+  fn picture(width: usize) -> Picture {
+    Picture {
+      width,
+      height: 1,
+      left_offset: 0,
+      top_offset: 0,
+      columns: vec![Vec::new(); width],
+    }
+  }
+
+  #[test]
+  fn choose_rotations() {
+    assert_eq!(rotation(0.0, 180.0), 1);
+    assert_eq!(rotation(0.0, 0.0), 5);
+    assert_eq!(rotation(0.0, 90.0), 3);
+    assert_eq!(rotation(0.0, 270.0), 7);
+    assert_eq!(rotation(90.0, 250.0), 1);
+    assert_eq!(rotation(90.0, 245.0), 2);
+  }
+
+  #[test]
+  fn parse_lump_names() {
+    assert_eq!(
+      parse_lump_name("TROOA2A8").unwrap(),
+      (
+        String::from("TROO"),
+        vec![
+          SpriteLump {
+            frame: 'A',
+            rotation: 2,
+            flip: false
+          },
+          SpriteLump {
+            frame: 'A',
+            rotation: 8,
+            flip: true
+          },
+        ]
+      )
+    );
+    assert_eq!(
+      parse_lump_name("bar1b0").unwrap(),
+      (
+        String::from("BAR1"),
+        vec![SpriteLump {
+          frame: 'B',
+          rotation: 0,
+          flip: false
+        }]
+      )
+    );
+    for name in &["TROO", "TROOA", "TROOA9", "TROOA1A", "TROOA0A0", "TROO_1"] {
+      assert!(
+        matches!(parse_lump_name(name), Err(DoomError::InvalidSprite { .. })),
+        "{}",
+        name
+      );
+    }
+  }
+
+  #[test]
+  fn find_frames() {
+    let mut sprites = Sprites::default();
+    sprites.insert("BAR1A0", picture(1)).unwrap();
+    sprites.insert("TROOA1", picture(2)).unwrap();
+    sprites.insert("TROOA2A8", picture(3)).unwrap();
+    sprites.insert("TROOA5", picture(4)).unwrap();
+
+    let width = |name, frame, rotation| {
+      sprites
+        .frame(name, frame, rotation)
+        .map(|(picture, flip)| (picture.width, flip))
+    };
+    assert_eq!(width("BAR1", 'A', 3), Some((1, false)));
+    assert_eq!(width("TROO", 'A', 1), Some((2, false)));
+    assert_eq!(width("TROO", 'A', 2), Some((3, false)));
+    assert_eq!(width("TROO", 'A', 8), Some((3, true)));
+    assert_eq!(width("TROO", 'A', 5), Some((4, false)));
+    assert_eq!(width("TROO", 'A', 3), None);
+    assert_eq!(width("TROO", 'B', 1), None);
+    assert_eq!(width("POSS", 'A', 1), None);
+  }
+
+  #[test]
+  fn replace_frames_with_later_lumps() {
+    let mut sprites = Sprites::default();
+    sprites.insert("BOSSA0", picture(1)).unwrap();
+    for name in &["BOSSA1", "BOSSA2A8", "BOSSA3A7", "BOSSA4A6", "BOSSA5"] {
+      sprites.insert(name, picture(2)).unwrap();
+    }
+    assert!(sprites.frames("BOSS").unwrap()[0].rotate);
+    assert_eq!(sprites.frame("BOSS", 'A', 7).unwrap().0.width, 2);
+    sprites.insert("BOSSA0", picture(3)).unwrap();
+    assert!(!sprites.frames("BOSS").unwrap()[0].rotate);
+    assert_eq!(sprites.frame("BOSS", 'A', 7).unwrap().0.width, 3);
+    assert!(sprites.validate().is_empty());
+  }
+
+  #[test]
+  fn report_missing_rotations_and_frames() {
+    let mut sprites = Sprites::default();
+    sprites.insert("TROOA1", picture(1)).unwrap();
+    sprites.insert("TROOA2A8", picture(1)).unwrap();
+    sprites.insert("BAR1A0", picture(1)).unwrap();
+    sprites.insert("BAR1C0", picture(1)).unwrap();
+    sprites.insert("POSSA0", picture(1)).unwrap();
+    let causes: Vec<_> = sprites
+      .validate()
+      .into_iter()
+      .map(|e| match e {
+        DoomError::InvalidSprite { sprite, cause } => (sprite, cause),
+        e => panic!("unexpected error {}", e),
+      })
+      .collect();
+    assert_eq!(
+      causes,
+      vec![
+        (String::from("BAR1"), String::from("is missing frame B")),
+        (
+          String::from("TROO"),
+          String::from("frame A is missing rotations 3, 4, 5, 6, 7")
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn load_sprites_from_wad() {
+    let picture = picture_lump(1, &[&[(0, &[9])]]);
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("TROOA1", &[]),
+        ("S_START", &[]),
+        ("BAR1A0", &picture),
+        ("BAR1B0", &picture),
+        ("S_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let sprites = Sprites::new(&wad).unwrap();
+    assert_eq!(sprites.frames("BAR1").unwrap().len(), 2);
+    assert!(sprites.frames("TROO").is_none());
+    assert_eq!(
+      sprites.frame("BAR1", 'B', 4).unwrap().0.columns[0][0].pixels,
+      vec![9]
+    );
+  }
+
+  #[test]
+  fn skip_broken_sprites() {
+    let picture = picture_lump(1, &[&[(0, &[9])]]);
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("S_START", &[]),
+        ("TROOA1", &picture),
+        ("BAR1A0", &picture),
+        ("BAR1", &picture),
+        ("POSSA0", &[1, 2]),
+        ("S_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let sprites = Sprites::new(&wad).unwrap();
+    assert!(sprites.frame("BAR1", 'A', 1).is_some());
+    assert!(sprites.frames("TROO").is_none());
+    assert!(sprites.frames("POSS").is_none());
+    let messages: Vec<_> = sprites
+      .diagnostics()
+      .iter()
+      .map(|diagnostic| diagnostic.message.as_str())
+      .collect();
+    assert_eq!(messages.len(), 3);
+    assert!(messages[0].contains("BAR1 is no valid"), "{}", messages[0]);
+    assert!(messages[1].contains("POSSA0"), "{}", messages[1]);
+    assert!(
+      messages[2].contains("TROO frame A is missing rotations 2"),
+      "{}",
+      messages[2]
+    );
+  }
+}
@@ -0,0 +1,399 @@
+use crate::datatypes::Result;
+use crate::errors::DoomError;
+use crate::wad::picture::Picture;
+use crate::wad::pnames::PNames;
+use crate::wad::texture::TextureDef;
+use crate::wad::{Namespace, Wad};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// The name of a missing texture on a sidedef.
+pub const NO_TEXTURE: &str = "-";
+
+/// The texture drawn wherever a ceiling shows the sky flat. It depends on
+/// the map, like in the original engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+  pub texture: &'static str,
+}
+
+impl Default for Sky {
+  fn default() -> Self {
+    Sky { texture: "SKY1" }
+  }
+}
+
+impl Sky {
+  /// Doom maps `ExMy` have one sky per episode, Doom II maps `MAPxx` switch
+  /// skies at MAP12 and MAP21.
+  pub fn new(map: &str) -> Self {
+    let map = map.to_uppercase();
+    let texture = if let Some(number) = map.strip_prefix("MAP") {
+      match number.parse::<u32>() {
+        Ok(number) if number >= 21 => "SKY3",
+        Ok(number) if number >= 12 => "SKY2",
+        _ => "SKY1",
+      }
+    } else {
+      match map.as_bytes() {
+        [b'E', b'2', b'M', ..] => "SKY2",
+        [b'E', b'3', b'M', ..] => "SKY3",
+        [b'E', b'4', b'M', ..] => "SKY4",
+        _ => "SKY1",
+      }
+    };
+    Sky { texture }
+  }
+}
+
+/// A composed wall texture, stored column by column. `opaque` holds the
+/// rows `start..end` of every column which are covered by a patch, like the
+/// posts of a picture. Everything else is only transparent on masked middle
+/// textures.
+#[derive(Debug)]
+pub struct Texture {
+  pub width: usize,
+  pub height: usize,
+  pub pixels: Vec<u8>,
+  pub opaque: Vec<Vec<(usize, usize)>>,
+}
+
+impl Texture {
+  /// A texture without any transparent pixels.
+  pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Self {
+    Texture {
+      width,
+      height,
+      pixels,
+      opaque: vec![vec![(0, height)]; width],
+    }
+  }
+
+  /// Draws the patches of a definition on top of each other. `patches`
+  /// holds the picture of every patch of the definition in the same order.
+  pub fn compose(definition: &TextureDef, patches: &[&Picture]) -> Self {
+    let (width, height) = (definition.width, definition.height);
+    let mut pixels = vec![0; width * height];
+    let mut covered = vec![false; width * height];
+    for (patch, picture) in definition.patches.iter().zip(patches) {
+      for (column, posts) in picture.columns.iter().enumerate() {
+        let x = i64::from(patch.origin_x) + column as i64;
+        if x < 0 || x >= width as i64 {
+          continue;
+        }
+        let x = x as usize;
+        for post in posts {
+          for (row, &pixel) in post.pixels.iter().enumerate() {
+            let y = i64::from(patch.origin_y) + (post.top_delta + row) as i64;
+            if y < 0 || y >= height as i64 {
+              continue;
+            }
+            pixels[x * height + y as usize] = pixel;
+            covered[x * height + y as usize] = true;
+          }
+        }
+      }
+    }
+
+    let mut opaque = Vec::with_capacity(width);
+    for x in 0..width {
+      let column = &covered[x * height..(x + 1) * height];
+      let mut rows = Vec::new();
+      let mut start = None;
+      for (y, &is_covered) in column.iter().enumerate() {
+        match (start, is_covered) {
+          (None, true) => start = Some(y),
+          (Some(top), false) => {
+            rows.push((top, y));
+            start = None;
+          }
+          _ => (),
+        }
+      }
+      if let Some(top) = start {
+        rows.push((top, height));
+      }
+      opaque.push(rows);
+    }
+
+    Texture {
+      width,
+      height,
+      pixels,
+      opaque,
+    }
+  }
+
+  fn column_index(&self, u: f32) -> usize {
+    (u.floor() as i64).rem_euclid(self.width as i64) as usize
+  }
+
+  /// Returns the column at the horizontal texture coordinate `u`, repeating
+  /// the texture in both directions.
+  pub fn column(&self, u: f32) -> &[u8] {
+    if self.width == 0 {
+      return &[];
+    }
+    let x = self.column_index(u);
+    &self.pixels[x * self.height..(x + 1) * self.height]
+  }
+
+  /// Returns the opaque rows of the column at `u`.
+  pub fn opaque_rows(&self, u: f32) -> &[(usize, usize)] {
+    if self.width == 0 {
+      return &[];
+    }
+    &self.opaque[self.column_index(u)]
+  }
+}
+
+/// Returns the texel at the vertical texture coordinate `v`, repeating the
+/// column. Empty columns are black.
+pub fn sample(column: &[u8], v: f32) -> u8 {
+  if column.is_empty() {
+    return 0;
+  }
+  column[(v.floor() as i64).rem_euclid(column.len() as i64) as usize]
+}
+
+/// All wall textures of the loaded wads by name.
+#[derive(Debug, Default)]
+pub struct Textures {
+  textures: HashMap<String, Texture>,
+}
+
+impl Textures {
+  /// Composes all textures of TEXTURE1 and TEXTURE2. Patches are looked up
+  /// between the patch markers first, like sprites and flats, and in the
+  /// whole wad otherwise. Wads without a PNAMES have no textures.
+  pub fn new(wad: &Wad) -> Result<Self> {
+    if wad.find_lump(&PNames::lump_name()).is_none() {
+      return Ok(Textures::default());
+    }
+    let pnames = PNames::new(wad.read_lump(&PNames::lump_name())?)
+      .map_err(|e| e.in_lump(&PNames::lump_name()))?;
+    let mut definitions = Vec::new();
+    for lump_name in TextureDef::lump_names().iter() {
+      if wad.find_lump(lump_name).is_some() {
+        let lump = wad.read_lump(lump_name)?;
+        definitions.extend(TextureDef::read_all(lump).map_err(|e| e.in_lump(lump_name))?);
+      }
+    }
+
+    // Only patches used by a texture are decoded, missing ones are reported
+    // while composing.
+    let mut patches = HashMap::new();
+    for patch in definitions
+      .iter()
+      .flat_map(|definition| definition.patches.iter())
+    {
+      let name = match pnames.names.get(patch.patch) {
+        Some(name) => name,
+        None => continue,
+      };
+      let index = wad
+        .find_lump_in(name, Namespace::Patches)
+        .or_else(|| wad.find_lump(name));
+      if let (Entry::Vacant(entry), Some(index)) = (patches.entry(name.clone()), index) {
+        entry.insert(Picture::new(wad.lump(index)?).map_err(|e| e.in_lump(name))?);
+      }
+    }
+    Textures::compose(&pnames, &definitions, &patches)
+  }
+
+  /// Composes the textures of TEXTURE1 and TEXTURE2 definitions. `patches`
+  /// holds the pictures of the PNAMES patches by name.
+  pub fn compose(
+    pnames: &PNames,
+    definitions: &[TextureDef],
+    patches: &HashMap<String, Picture>,
+  ) -> Result<Self> {
+    let mut textures = Textures::default();
+    for definition in definitions {
+      let mut pictures = Vec::with_capacity(definition.patches.len());
+      for patch in definition.patches.iter() {
+        let name = pnames
+          .names
+          .get(patch.patch)
+          .ok_or(DoomError::InvalidIndex {
+            kind: "patch",
+            index: patch.patch,
+            max: pnames.names.len(),
+          })?;
+        let picture = patches.get(name).ok_or_else(|| DoomError::PatchMissing {
+          texture: definition.name.clone(),
+          patch: name.clone(),
+        })?;
+        pictures.push(picture);
+      }
+      // The first texture with a name wins, like in the original engine.
+      if textures.get(&definition.name).is_none() {
+        textures.insert(&definition.name, Texture::compose(definition, &pictures));
+      }
+    }
+    Ok(textures)
+  }
+
+  pub fn insert(&mut self, name: &str, texture: Texture) {
+    self.textures.insert(name.to_uppercase(), texture);
+  }
+
+  pub fn get(&self, name: &str) -> Option<&Texture> {
+    self.textures.get(&name.to_uppercase())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::render::texture::{Sky, Texture, Textures};
+  use crate::wad::picture::tests::picture_lump;
+  use crate::wad::picture::{Picture, Post};
+  use crate::wad::pnames::PNames;
+  use crate::wad::tests::build_wad;
+  use crate::wad::texture::tests::texture_lump;
+  use crate::wad::texture::TextureDef;
+  use crate::wad::Wad;
+  use std::collections::HashMap;
+
+  #[test]
+  fn choose_sky_by_map() {
+    let sky = |map| Sky::new(map).texture;
+    assert_eq!(sky("E1M1"), "SKY1");
+    assert_eq!(sky("E2M3"), "SKY2");
+    assert_eq!(sky("E3M8"), "SKY3");
+    assert_eq!(sky("e4m1"), "SKY4");
+    assert_eq!(sky("MAP01"), "SKY1");
+    assert_eq!(sky("MAP11"), "SKY1");
+    assert_eq!(sky("MAP12"), "SKY2");
+    assert_eq!(sky("MAP20"), "SKY2");
+    assert_eq!(sky("MAP21"), "SKY3");
+    assert_eq!(sky("MAP32"), "SKY3");
+    assert_eq!(sky("TEST"), "SKY1");
+  }
+
+  /// A picture whose columns are lists of `(top_delta, pixels)` posts.
+  fn picture(height: usize, columns: &[&[(usize, &[u8])]]) -> Picture {
+    Picture {
+      width: columns.len(),
+      height,
+      left_offset: 0,
+      top_offset: 0,
+      columns: columns
+        .iter()
+        .map(|posts| {
+          posts
+            .iter()
+            .map(|(top_delta, pixels)| Post {
+              top_delta: *top_delta,
+              pixels: pixels.to_vec(),
+            })
+            .collect()
+        })
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn compose_patches() {
+    let definition =
+      &TextureDef::read_all(&texture_lump(&[("GRATE", 3, 4, &[(0, 0, 0), (1, 1, 1)])])).unwrap()[0];
+    let wide = picture(4, &[&[(0, &[1, 1])], &[(3, &[2])]]);
+    let small = picture(2, &[&[(0, &[3, 3])], &[(0, &[4, 4])]]);
+    let texture = Texture::compose(definition, &[&wide, &small]);
+    assert_eq!((texture.width, texture.height), (3, 4));
+    assert_eq!(texture.column(0.0), &[1, 1, 0, 0]);
+    // The second patch is drawn on top of the first one.
+    assert_eq!(texture.column(1.0), &[0, 3, 3, 2]);
+    assert_eq!(texture.column(2.0), &[0, 4, 4, 0]);
+    assert_eq!(texture.opaque_rows(0.0), &[(0, 2)]);
+    assert_eq!(texture.opaque_rows(1.0), &[(1, 4)]);
+    assert_eq!(texture.opaque_rows(2.0), &[(1, 3)]);
+  }
+
+  #[test]
+  fn compose_textures_by_name() {
+    let pnames = PNames {
+      names: vec![String::from("PATCH1"), String::from("PATCH2")],
+    };
+    let definitions = TextureDef::read_all(&texture_lump(&[
+      ("WALL", 1, 2, &[(0, 0, 0)]),
+      ("WALL", 1, 2, &[(0, 0, 1)]),
+      ("DOOR", 1, 2, &[(0, 0, 1)]),
+    ]))
+    .unwrap();
+    let mut patches = HashMap::new();
+    patches.insert(String::from("PATCH1"), picture(2, &[&[(0, &[5, 6])]]));
+    patches.insert(String::from("PATCH2"), picture(2, &[&[(0, &[7, 8])]]));
+    let textures = Textures::compose(&pnames, &definitions, &patches).unwrap();
+    assert_eq!(textures.get("wall").unwrap().column(0.0), &[5, 6]);
+    assert_eq!(textures.get("DOOR").unwrap().column(0.0), &[7, 8]);
+  }
+
+  #[test]
+  fn report_missing_patches() {
+    let pnames = PNames {
+      names: vec![String::from("PATCH1")],
+    };
+    let definitions = TextureDef::read_all(&texture_lump(&[
+      ("WALL", 1, 2, &[(0, 0, 0)]),
+      ("DOOR", 1, 2, &[(0, 0, 1)]),
+    ]))
+    .unwrap();
+    let mut patches = HashMap::new();
+    assert!(matches!(
+      Textures::compose(&pnames, &definitions, &patches),
+      Err(DoomError::PatchMissing { ref texture, ref patch }) if texture == "WALL" && patch == "PATCH1"
+    ));
+    patches.insert(String::from("PATCH1"), picture(2, &[&[(0, &[5, 6])]]));
+    assert!(matches!(
+      Textures::compose(&pnames, &definitions, &patches),
+      Err(DoomError::InvalidIndex {
+        kind: "patch",
+        index: 1,
+        ..
+      })
+    ));
+  }
+
+  fn pnames(names: &[&str]) -> Vec<u8> {
+    let mut lump = Vec::from(&(names.len() as u32).to_le_bytes()[..]);
+    for name in names {
+      let mut patch_name = [0u8; 8];
+      patch_name[..name.len()].copy_from_slice(name.as_bytes());
+      lump.extend_from_slice(&patch_name);
+    }
+    lump
+  }
+
+  #[test]
+  fn load_textures_from_wad() {
+    let patch = picture_lump(2, &[&[(0, &[5, 6])]]);
+    let texture1 = texture_lump(&[("WALL", 1, 2, &[(0, 0, 0)])]);
+    let texture2 = texture_lump(&[("DOOR", 1, 2, &[(0, 0, 1)])]);
+    let other = picture_lump(2, &[&[(0, &[7, 8])]]);
+    let lumps: &[(&str, &[u8])] = &[
+      ("PNAMES", &pnames(&["PATCH1", "patch2"])),
+      ("TEXTURE1", &texture1),
+      ("TEXTURE2", &texture2),
+      ("P_START", &[]),
+      ("PATCH1", &patch),
+      ("P_END", &[]),
+      ("PATCH2", &other),
+    ];
+    let textures = Textures::new(&Wad::new(&build_wad("IWAD", lumps)).unwrap()).unwrap();
+    assert_eq!(textures.get("wall").unwrap().column(0.0), &[5, 6]);
+    assert_eq!(textures.get("DOOR").unwrap().column(0.0), &[7, 8]);
+
+    let missing = Wad::new(&build_wad("IWAD", &lumps[..3])).unwrap();
+    assert!(matches!(
+      Textures::new(&missing),
+      Err(DoomError::PatchMissing { .. })
+    ));
+    let without_pnames = Wad::new(&build_wad("IWAD", &lumps[1..])).unwrap();
+    assert!(Textures::new(&without_pnames)
+      .unwrap()
+      .get("WALL")
+      .is_none());
+  }
+}
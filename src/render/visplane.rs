@@ -0,0 +1,132 @@
+use crate::render::flat::SKY_FLAT;
+
+/// The visible part of a floor or ceiling. Every column holds the rows
+/// `top..bottom` which still have to be filled with the flat.
+#[derive(Debug)]
+pub struct Visplane {
+  pub height: i16,
+  pub texture: String,
+  pub light_level: i16,
+  pub min_x: usize,
+  pub max_x: usize,
+  pub rows: Vec<Option<(usize, usize)>>,
+}
+
+impl Visplane {
+  fn is_free(&self, x1: usize, x2: usize) -> bool {
+    self.rows[x1..x2].iter().all(Option::is_none)
+  }
+}
+
+/// All visplanes of a frame. Planes with the same height, texture and light
+/// level are merged as long as their columns don't overlap.
+#[derive(Debug)]
+pub struct Visplanes {
+  width: usize,
+  pub planes: Vec<Visplane>,
+}
+
+impl Visplanes {
+  pub fn new(width: usize) -> Self {
+    Visplanes {
+      width,
+      planes: Vec::new(),
+    }
+  }
+
+  /// Returns the plane which is going to be marked in the columns `x1..x2`.
+  pub fn find(
+    &mut self,
+    height: i16,
+    texture: &str,
+    light_level: i16,
+    x1: usize,
+    x2: usize,
+  ) -> usize {
+    // The sky looks the same everywhere.
+    let (height, light_level) = if texture == SKY_FLAT {
+      (0, 0)
+    } else {
+      (height, light_level)
+    };
+    let found = self.planes.iter().position(|plane| {
+      plane.height == height
+        && plane.light_level == light_level
+        && plane.texture == texture
+        && plane.is_free(x1, x2)
+    });
+    let id = match found {
+      Some(id) => id,
+      None => {
+        self.planes.push(Visplane {
+          height,
+          texture: String::from(texture),
+          light_level,
+          min_x: x1,
+          max_x: x2,
+          rows: vec![None; self.width],
+        });
+        self.planes.len() - 1
+      }
+    };
+    let plane = &mut self.planes[id];
+    plane.min_x = plane.min_x.min(x1);
+    plane.max_x = plane.max_x.max(x2);
+    id
+  }
+
+  /// Marks the rows from `top` up to (not including) `bottom` of a column,
+  /// rounded like `FrameBuffer::draw_column`.
+  pub fn mark(&mut self, id: usize, x: usize, top: f32, bottom: f32) {
+    let top = top.ceil().max(0.0) as usize;
+    let bottom = bottom.ceil().max(0.0) as usize;
+    if top < bottom {
+      self.planes[id].rows[x] = Some((top, bottom));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::render::visplane::Visplanes;
+
+  #[test]
+  fn merge_planes_without_overlapping_columns() {
+    let mut visplanes = Visplanes::new(320);
+    let a = visplanes.find(0, "FLOOR", 160, 0, 100);
+    visplanes.mark(a, 50, 120.0, 200.0);
+    assert_eq!(visplanes.find(0, "FLOOR", 160, 100, 200), a);
+    assert_eq!(visplanes.planes[a].min_x, 0);
+    assert_eq!(visplanes.planes[a].max_x, 200);
+    assert_eq!(visplanes.planes[a].rows[50], Some((120, 200)));
+  }
+
+  #[test]
+  fn split_planes_with_overlapping_columns() {
+    let mut visplanes = Visplanes::new(320);
+    let a = visplanes.find(0, "FLOOR", 160, 0, 100);
+    visplanes.mark(a, 50, 120.0, 200.0);
+    assert_ne!(visplanes.find(0, "FLOOR", 160, 40, 60), a);
+    assert_ne!(visplanes.find(8, "FLOOR", 160, 100, 200), a);
+    assert_ne!(visplanes.find(0, "NUKAGE1", 160, 100, 200), a);
+    assert_ne!(visplanes.find(0, "FLOOR", 128, 100, 200), a);
+    assert_eq!(visplanes.planes.len(), 5);
+  }
+
+  #[test]
+  fn merge_all_skies() {
+    let mut visplanes = Visplanes::new(320);
+    let a = visplanes.find(128, "F_SKY1", 160, 0, 100);
+    assert_eq!(visplanes.find(256, "F_SKY1", 255, 100, 200), a);
+  }
+
+  #[test]
+  fn ignore_empty_rows() {
+    let mut visplanes = Visplanes::new(320);
+    let a = visplanes.find(0, "FLOOR", 160, 0, 100);
+    visplanes.mark(a, 50, 120.2, 120.9);
+    visplanes.mark(a, 51, 130.0, 120.0);
+    assert_eq!(visplanes.planes[a].rows[50], None);
+    assert_eq!(visplanes.planes[a].rows[51], None);
+  }
+}
@@ -1,36 +1,127 @@
-use crate::datatypes::Result;
+use crate::datatypes::{MapLumpsIndex, Result};
 use crate::errors::DoomError;
 use crate::map::Map;
+use crate::render::texture::Sky;
+use crate::validation::{Diagnostic, ValidationMode, Validator};
+use crate::wad::blockmap::Blockmap;
+use crate::wad::linedef::LineDef;
 use crate::wad::node::Node;
+use crate::wad::reject::Reject;
+use crate::wad::sector::Sector;
 use crate::wad::seg::Seg;
+use crate::wad::sidedef::SideDef;
 use crate::wad::ssector::SSector;
+use crate::wad::thing::Thing;
+use crate::wad::vertex::{Vertex, VisibleVertexes};
 use crate::wad::Wad;
 use specs::World;
-use crate::wad::vertex::{Vertex, VisibleVertexes};
-use crate::wad::linedef::LineDef;
 
-pub fn create_map(map: &str, wad: &Wad, ecs: &mut World) -> Result<Map> {
+pub fn create_map(map: &str, wad: &Wad, ecs: &mut World, mode: ValidationMode) -> Result<Map> {
   match wad.find_map_index(map) {
     Some(map_index) => {
+      let things: Vec<Thing> = wad.read_wad_for(map_index)?;
       let nodes: Vec<Node> = wad.read_wad_for(map_index)?;
-      ecs.insert(nodes);
       let segs: Vec<Seg> = wad.read_wad_for(map_index)?;
-      ecs.insert(segs);
       let ssector: Vec<SSector> = wad.read_wad_for(map_index)?;
-      ecs.insert(ssector);
       let vertexes: Vec<Vertex> = wad.read_wad_for(map_index)?;
-      ecs.insert(vertexes);
       let line_defs: Vec<LineDef> = wad.read_wad_for(map_index)?;
+      let side_defs: Vec<SideDef> = wad.read_wad_for(map_index)?;
+      let sectors: Vec<Sector> = wad.read_wad_for(map_index)?;
+      // Maps without a REJECT lump, or with one cut short, see everything.
+      let reject = match wad.read_map_lump(
+        map_index,
+        MapLumpsIndex::REJECT as usize,
+        &Reject::lump_name(),
+      ) {
+        Ok(lump) => lump,
+        Err(DoomError::LumpMissing { .. }) | Err(DoomError::Truncated { .. }) => &[],
+        Err(e) => return Err(e),
+      };
+      let reject = Reject::new(reject, sectors.len());
+      let blockmap: Blockmap = wad.read_lump_for(map_index)?;
+      // TODO: I create vertexes and linedefs two times because of borrowing issues.
+      let loaded_map = Map::read_at(wad, map_index)?;
+
+      let diagnostics: Vec<Diagnostic> = Validator {
+        things: &things,
+        line_defs: &line_defs,
+        side_defs: &side_defs,
+        vertexes: &vertexes,
+        segs: &segs,
+        ssectors: &ssector,
+        nodes: &nodes,
+        sectors: &sectors,
+      }
+      .validate();
+      // Nothing is inserted before every lump has been read, so a broken map
+      // leaves the loaded one intact. Only the diagnostics are stored before
+      // a strict failure, so they can still be fetched.
+      ecs.insert(diagnostics.clone());
+      if diagnostics
+        .iter()
+        .any(|diagnostic| mode.rejects(diagnostic))
+      {
+        return Err(DoomError::InvalidMap {
+          map: String::from(map),
+          diagnostics,
+        });
+      }
+
+      ecs.insert(nodes);
+      ecs.insert(segs);
+      ecs.insert(ssector);
+      ecs.insert(vertexes);
       ecs.insert(line_defs);
-      let visible_vertexes :VisibleVertexes = Default::default();
+      ecs.insert(side_defs);
+      ecs.insert(reject);
+      ecs.insert(Sky::new(map));
+      ecs.insert(sectors);
+      ecs.insert(blockmap);
+      let visible_vertexes: VisibleVertexes = Default::default();
       ecs.insert(visible_vertexes);
-      // TODO: I create vertexes and linedefs two times because of borrowing issues.
-      Ok(Map::new(
-        wad.read_wad_for::<Vertex>(map_index)?,
-        wad.read_wad_for::<LineDef>(map_index)?,
-      ))
+      Ok(loaded_map)
     }
 
-    None => Err(DoomError::Wad(format!("Failed to load MAP: {}", map))),
+    None => Err(DoomError::MapNotFound(String::from(map))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::resource::create_map;
+  use crate::validation::{Diagnostic, ValidationMode};
+  use crate::wad::node::Node;
+  use crate::wad::reject::Reject;
+  use crate::wad::tests::build_wad;
+  use crate::wad::Wad;
+  use specs::prelude::*;
+
+  fn map_wad(reject: &str, blockmap: &[u8]) -> Wad {
+    let mut lumps: Vec<(&str, &[u8])> = [
+      "E1M1", "THINGS", "LINEDEFS", "SIDEDEFS", "VERTEXES", "SEGS", "SSECTORS", "NODES", "SECTORS",
+      reject,
+    ]
+    .iter()
+    .map(|&name| (name, &[][..]))
+    .collect();
+    lumps.push(("BLOCKMAP", blockmap));
+    Wad::new(&build_wad("IWAD", &lumps)).unwrap()
+  }
+
+  #[test]
+  fn keep_the_world_on_broken_maps() {
+    let wad = map_wad("REJECT", &[0, 0, 0]);
+    let mut world = World::new();
+    assert!(create_map("E1M1", &wad, &mut world, ValidationMode::Lenient).is_err());
+    assert!(!world.has_value::<Vec<Node>>());
+    assert!(!world.has_value::<Vec<Diagnostic>>());
+  }
+
+  #[test]
+  fn see_everything_without_reject() {
+    let wad = map_wad("DUMMY", &[0; 8]);
+    let mut world = World::new();
+    create_map("E1M1", &wad, &mut world, ValidationMode::Lenient).unwrap();
+    assert!(world.read_resource::<Reject>().can_see(0, 1));
   }
 }
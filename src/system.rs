@@ -1,3 +1,4 @@
+pub(crate) mod animation;
 pub(crate) mod keyboard;
 pub(crate) mod physics;
 pub(crate) mod view;
@@ -0,0 +1,14 @@
+use crate::render::flat::Flats;
+use specs::prelude::*;
+
+/// Every tick is one tic of the original engine, so `Doom::tick` has to be
+/// called 35 times a second for animations to run at their original speed.
+pub struct Animation;
+
+impl<'a> System<'a> for Animation {
+  type SystemData = WriteExpect<'a, Flats>;
+
+  fn run(&mut self, mut flats: Self::SystemData) {
+    flats.tick();
+  }
+}
@@ -1,19 +1,112 @@
 use crate::angle::Angle;
 use crate::component::*;
-use crate::wad::node::Node;
+use crate::render::camera::{Camera, VIEW_HEIGHT};
+use crate::render::flat::Flats;
+use crate::render::frame_buffer::FrameBuffer;
+use crate::render::lighting::Lighting;
+use crate::render::sprite::Sprites;
+use crate::render::texture::{Sky, Textures};
+use crate::render::visplane::Visplanes;
+use crate::render::{is_solid, ColumnClips, MapThing, Renderer, VisibleSeg};
+use crate::wad::linedef::{LineDef, NO_SIDE_DEF};
+use crate::wad::node::{BoundingBox, Node};
+use crate::wad::sector::Sector;
 use crate::wad::seg::Seg;
+use crate::wad::sidedef::SideDef;
 use crate::wad::ssector::SSector;
 use crate::wad::vertex::{Vertex, VisibleVertexes};
 use specs::prelude::*;
 use std::f32::consts::PI;
 
+/// The screen column ranges `start..end` which are already covered by solid
+/// walls, sorted from left to right.
+struct SolidSegs {
+  width: usize,
+  ranges: Vec<(usize, usize)>,
+}
+
+impl SolidSegs {
+  fn new(width: usize) -> Self {
+    SolidSegs {
+      width,
+      ranges: Vec::new(),
+    }
+  }
+
+  fn is_full(&self) -> bool {
+    self.ranges == [(0, self.width)]
+  }
+
+  fn is_covered(&self, x1: usize, x2: usize) -> bool {
+    self
+      .ranges
+      .iter()
+      .any(|&(start, end)| start <= x1 && end >= x2)
+  }
+
+  /// Returns the parts of `x1..x2` which aren't covered yet. Solid walls are
+  /// added to the covered ranges.
+  fn clip(&mut self, x1: usize, x2: usize, solid: bool) -> Vec<(usize, usize)> {
+    let mut fragments = Vec::new();
+    let mut x = x1;
+    for &(start, end) in &self.ranges {
+      if end <= x {
+        continue;
+      }
+      if start >= x2 {
+        break;
+      }
+      if start > x {
+        fragments.push((x, start));
+      }
+      x = end;
+      if x >= x2 {
+        break;
+      }
+    }
+    if x < x2 {
+      fragments.push((x, x2));
+    }
+
+    if solid && !fragments.is_empty() {
+      self.ranges.push((x1, x2));
+      self.ranges.sort_unstable();
+      let mut merged: Vec<(usize, usize)> = Vec::with_capacity(self.ranges.len());
+      for &(start, end) in &self.ranges {
+        match merged.last_mut() {
+          Some(last) if start <= last.1 => last.1 = last.1.max(end),
+          _ => merged.push((start, end)),
+        }
+      }
+      self.ranges = merged;
+    }
+    fragments
+  }
+}
+
+/// The part of a seg inside the field of view. The angles are relative to the
+/// view direction and positive to the left, the columns span `x1..x2`.
+#[derive(Debug, PartialEq)]
+struct ClippedSeg {
+  left: f32,
+  right: f32,
+  x1: usize,
+  x2: usize,
+}
+
 struct ViewFinder<'a> {
   nodes: &'a Vec<Node>,
   segs: &'a Vec<Seg>,
   ssectros: &'a Vec<SSector>,
   vertexes: &'a Vec<Vertex>,
+  line_defs: &'a Vec<LineDef>,
+  side_defs: &'a Vec<SideDef>,
+  sectors: &'a Vec<Sector>,
   player: (&'a Rotation, &'a Position, &'a KeyboardControlled),
+  camera: Camera,
+  solid_segs: SolidSegs,
   result: VisibleVertexes,
+  visible_segs: Vec<VisibleSeg>,
 }
 
 impl<'a> ViewFinder<'a> {
@@ -27,79 +120,224 @@ impl<'a> ViewFinder<'a> {
     node_id & !SUB_SECTOR_IDENTIFIER
   }
 
+  // A map with a single sub sector doesn't need any nodes.
+  fn root_node(nodes: &[Node]) -> usize {
+    match nodes.len() {
+      0 => 0x8000,
+      len => len - 1,
+    }
+  }
 
-  fn is_point_on_left_side(&self, node_id: usize) -> bool {
-    let node = &self.nodes[node_id];
-    let pos = self.player.1;
-    let dx = pos.x - node.x_partition;
-    let dy = pos.y - node.y_partition;
-    (((dx * node.change_y_partition) - (dy * node.change_x_partition)) <= 0)
+  fn is_point_on_left_side(node: &Node, x: i16, y: i16) -> bool {
+    let dx = i32::from(x) - i32::from(node.x_partition);
+    let dy = i32::from(y) - i32::from(node.y_partition);
+    (dx * i32::from(node.change_y_partition)) - (dy * i32::from(node.change_x_partition)) <= 0
+  }
+
+  fn find_sub_sector(nodes: &[Node], x: i16, y: i16) -> usize {
+    let mut node_id = ViewFinder::root_node(nodes);
+    while !ViewFinder::check_for_sub_sector(node_id) {
+      let node = &nodes[node_id];
+      node_id = if ViewFinder::is_point_on_left_side(node, x, y) {
+        node.left_child as usize
+      } else {
+        node.right_child as usize
+      };
+    }
+    ViewFinder::get_sub_sector(node_id)
   }
 
   fn vertex_to_angle(&self, vertex: &Vertex) -> Angle {
     let position = self.player.1;
-    let dx = f32::from(vertex.x - position.x);
-    let dy = f32::from(vertex.y - position.y);
+    let dx = f32::from(vertex.x) - f32::from(position.x);
+    let dy = f32::from(vertex.y) - f32::from(position.y);
     Angle::new(dy.atan2(dx) * 180.0 / PI)
   }
 
-  fn clip_vertexes_in_fov(&self, v1: &Vertex, v2: &Vertex) -> bool {
+  /// Clips the seg `v1 -> v2` to the field of view. Returns `None` if the
+  /// seg faces away from the player or lies completely outside of the view.
+  fn clip_vertexes_in_fov(&self, v1: &Vertex, v2: &Vertex) -> Option<ClippedSeg> {
     let rot = self.player.0;
     let fov = f32::from(rot.fov);
     let half_fov = fov / 2.0;
-    let mut v1_angle = self.vertex_to_angle(v1);
-    let mut v2_angle = self.vertex_to_angle(v2);
-    let delta = v1_angle - v2_angle;
+    let v1_angle = self.vertex_to_angle(v1);
+    let v2_angle = self.vertex_to_angle(v2);
+    let span = (v1_angle - v2_angle).degrees();
 
-    if delta >= 180.0 {
-      return false;
+    // The start vertex of a seg facing the player is always on the left.
+    if span >= 180.0 {
+      return None;
     }
 
-    v1_angle -= rot.angle;
-    v2_angle -= rot.angle;
+    let mut left = (v1_angle - rot.angle).degrees();
+    let mut right = (v2_angle - rot.angle).degrees();
 
-    let mut v1_moved = v1_angle + half_fov;
+    // Both angles are moved so that the FOV covers 0..fov. Anything beyond
+    // that is outside and has to be clipped to the edge of the view.
+    let left_moved = Angle::new(left + half_fov).degrees();
+    if left_moved > fov {
+      if left_moved - fov >= span {
+        return None;
+      }
+      left = half_fov;
+    }
+    let right_moved = Angle::new(half_fov - right).degrees();
+    if right_moved > fov {
+      if right_moved - fov >= span {
+        return None;
+      }
+      right = -half_fov;
+    }
 
-    if v1_moved > fov {
-      v1_moved = v1_moved - fov;
+    let signed = |angle: f32| if angle > 180.0 { angle - 360.0 } else { angle };
+    let (left, right) = (signed(left), signed(right));
+    let x1 = self.camera.angle_to_x(left);
+    let x2 = self.camera.angle_to_x(right);
+    if x1 >= x2 {
+      return None;
+    }
+    Some(ClippedSeg {
+      left,
+      right,
+      x1,
+      x2,
+    })
+  }
+
+  /// The points where the view rays at the clipped angles hit the seg.
+  fn clipped_vertexes(&self, v1: &Vertex, v2: &Vertex, clipped: &ClippedSeg) -> (Vertex, Vertex) {
+    let rot = self.player.0;
+    let half_fov = f32::from(rot.fov) / 2.0;
+    let position = self.player.1;
+    let (px, py) = (f32::from(position.x), f32::from(position.y));
+    let (ax, ay) = (f32::from(v1.x), f32::from(v1.y));
+    let (ex, ey) = (f32::from(v2.x) - ax, f32::from(v2.y) - ay);
 
-      if v1_moved >= delta {
-        return false;
+    let hit = |angle: f32, vertex: &Vertex| {
+      if angle.abs() < half_fov {
+        return *vertex;
       }
-    }
-    true
+      let (dy, dx) = (rot.angle.degrees() + angle).to_radians().sin_cos();
+      let denominator = dx * ey - dy * ex;
+      if denominator.abs() < f32::EPSILON {
+        return *vertex;
+      }
+      let t = (((ax - px) * dy - (ay - py) * dx) / denominator).clamp(0.0, 1.0);
+      Vertex {
+        x: (ax + t * ex).round() as i16,
+        y: (ay + t * ey).round() as i16,
+      }
+    };
+    (hit(clipped.left, v1), hit(clipped.right, v2))
   }
 
   fn find_vertexes_in_sub_sector(&mut self, ssector_id: usize) {
     let ssector = &self.ssectros[ssector_id];
     for i in 0..ssector.seg_count {
-      let seg = &self.segs[(ssector.first_seg + i) as usize];
+      let seg_id = (ssector.first_seg + i) as usize;
+      let seg = &self.segs[seg_id];
       let v1 = &self.vertexes[seg.start_vertex as usize];
       let v2 = &self.vertexes[seg.end_vertex as usize];
 
-      if self.clip_vertexes_in_fov(v1, v2) {
-        self.result.vertexes.push((*v1, *v2));
+      if let Some(clipped) = self.clip_vertexes_in_fov(v1, v2) {
+        let fragments = self
+          .solid_segs
+          .clip(clipped.x1, clipped.x2, self.is_solid(seg));
+        if !fragments.is_empty() {
+          let vertexes = self.clipped_vertexes(v1, v2, &clipped);
+          self.result.vertexes.push(vertexes);
+        }
+        for (x1, x2) in fragments {
+          self.visible_segs.push(VisibleSeg {
+            seg: seg_id,
+            x1,
+            x2,
+          });
+        }
       }
     }
   }
 
+  fn is_solid(&self, seg: &Seg) -> bool {
+    let (front, back) = seg.side_defs(self.line_defs);
+    let sector = |side_def: u16| &self.sectors[self.side_defs[side_def as usize].sector as usize];
+    let back = match back {
+      NO_SIDE_DEF => None,
+      back => Some(sector(back)),
+    };
+    is_solid(sector(front), back)
+  }
+
+  /// Checks if anything inside the box could be visible. The box is reduced
+  /// to the two corners which span its silhouette as seen from the player,
+  /// which is then clipped like a seg.
+  fn check_bbox(&self, bbox: BoundingBox) -> bool {
+    let pos = self.player.1;
+    let column = if pos.x <= bbox.left {
+      0
+    } else if pos.x < bbox.right {
+      1
+    } else {
+      2
+    };
+    let row = if pos.y >= bbox.top {
+      0
+    } else if pos.y > bbox.bottom {
+      1
+    } else {
+      2
+    };
+
+    let ((x1, y1), (x2, y2)) = match (column, row) {
+      (0, 0) => ((bbox.right, bbox.top), (bbox.left, bbox.bottom)),
+      (1, 0) => ((bbox.right, bbox.top), (bbox.left, bbox.top)),
+      (2, 0) => ((bbox.right, bbox.bottom), (bbox.left, bbox.top)),
+      (0, 1) => ((bbox.left, bbox.top), (bbox.left, bbox.bottom)),
+      (2, 1) => ((bbox.right, bbox.bottom), (bbox.right, bbox.top)),
+      (0, 2) => ((bbox.left, bbox.top), (bbox.right, bbox.bottom)),
+      (1, 2) => ((bbox.left, bbox.bottom), (bbox.right, bbox.bottom)),
+      (2, 2) => ((bbox.left, bbox.bottom), (bbox.right, bbox.top)),
+      // The player is inside the box.
+      _ => return true,
+    };
+    let v1 = Vertex { x: x1, y: y1 };
+    let v2 = Vertex { x: x2, y: y2 };
+
+    if (self.vertex_to_angle(&v1) - self.vertex_to_angle(&v2)).degrees() >= 180.0 {
+      return true;
+    }
+    match self.clip_vertexes_in_fov(&v1, &v2) {
+      Some(clipped) => !self.solid_segs.is_covered(clipped.x1, clipped.x2),
+      None => false,
+    }
+  }
+
   fn bsp(&mut self, node_id: usize) {
+    if self.solid_segs.is_full() {
+      return;
+    }
+
     if ViewFinder::check_for_sub_sector(node_id) {
       self.find_vertexes_in_sub_sector(ViewFinder::get_sub_sector(node_id));
       return;
     }
 
-    if self.is_point_on_left_side(node_id) {
-      self.bsp(self.nodes[node_id].left_child as usize);
-      self.bsp(self.nodes[node_id].right_child as usize);
+    let pos = self.player.1;
+    let node = &self.nodes[node_id];
+    if ViewFinder::is_point_on_left_side(node, pos.x, pos.y) {
+      self.bsp(node.left_child as usize);
+      if self.check_bbox(node.right_box()) {
+        self.bsp(node.right_child as usize);
+      }
     } else {
-      self.bsp(self.nodes[node_id].right_child as usize);
-      self.bsp(self.nodes[node_id].left_child as usize);
+      self.bsp(node.right_child as usize);
+      if self.check_bbox(node.left_box()) {
+        self.bsp(node.left_child as usize);
+      }
     }
   }
 }
 
-
 pub struct View;
 
 impl<'a> System<'a> for View {
@@ -112,6 +350,16 @@ impl<'a> System<'a> for View {
     ReadExpect<'a, Vec<SSector>>,
     ReadExpect<'a, Vec<Vertex>>,
     WriteExpect<'a, VisibleVertexes>,
+    ReadExpect<'a, Vec<LineDef>>,
+    ReadExpect<'a, Vec<SideDef>>,
+    ReadExpect<'a, Vec<Sector>>,
+    WriteExpect<'a, FrameBuffer>,
+    ReadExpect<'a, Textures>,
+    ReadExpect<'a, Flats>,
+    ReadExpect<'a, Lighting>,
+    ReadStorage<'a, Sprite>,
+    ReadExpect<'a, Sprites>,
+    ReadExpect<'a, Sky>,
   );
 
   fn run(&mut self, mut data: Self::SystemData) {
@@ -119,16 +367,690 @@ impl<'a> System<'a> for View {
     let segs = &*data.4;
     let ssectros = &*data.5;
     let vertexes = &*data.6;
+    let line_defs = &*data.8;
+    let side_defs = &*data.9;
+    let sectors = &*data.10;
+    let frame_buffer = &mut *data.11;
+    let textures = &*data.12;
+    let flats = &*data.13;
+    let lighting = &*data.14;
+    let sprites = &*data.16;
+    let sky = *data.17;
     let player = (&data.0, &data.1, &data.2).join().collect::<Vec<_>>()[0];
+
+    let sector_at = |x: i16, y: i16| {
+      let ssector = &ssectros[ViewFinder::find_sub_sector(nodes, x, y)];
+      let (side_def, _) = segs[ssector.first_seg as usize].side_defs(line_defs);
+      &sectors[side_defs[side_def as usize].sector as usize]
+    };
+    let (rot, pos, _) = player;
+    let camera = Camera {
+      x: f32::from(pos.x),
+      y: f32::from(pos.y),
+      z: f32::from(sector_at(pos.x, pos.y).floor_height) + VIEW_HEIGHT,
+      angle: rot.angle.degrees(),
+      fov: f32::from(rot.fov),
+      width: frame_buffer.width,
+      height: frame_buffer.height,
+    };
+
     let mut view_finder = ViewFinder {
       nodes,
       segs,
       ssectros,
       vertexes,
+      line_defs,
+      side_defs,
+      sectors,
       player,
+      camera,
+      solid_segs: SolidSegs::new(frame_buffer.width),
       result: Default::default(),
+      visible_segs: Vec::new(),
     };
-    view_finder.bsp(nodes.len() - 1);
+    view_finder.bsp(ViewFinder::root_node(nodes));
+
+    frame_buffer.clear();
+    let mut renderer = Renderer {
+      camera,
+      segs,
+      line_defs,
+      side_defs,
+      sectors,
+      vertexes,
+      textures,
+      sky,
+      flats,
+      lighting,
+      clips: ColumnClips::new(frame_buffer.width, frame_buffer.height),
+      sprites,
+      visplanes: Visplanes::new(frame_buffer.width),
+      draw_segs: Vec::new(),
+      frame_buffer,
+    };
+    renderer.draw_walls(&view_finder.visible_segs);
+    renderer.draw_planes();
+
+    let things = (&data.1, &data.15)
+      .join()
+      .map(|(pos, sprite)| {
+        let sector = sector_at(pos.x, pos.y);
+        MapThing {
+          x: f32::from(pos.x),
+          y: f32::from(pos.y),
+          z: f32::from(sector.floor_height),
+          angle: sprite.angle.degrees(),
+          sprite: &sprite.name,
+          frame: sprite.frame,
+          light_level: sector.light_level,
+        }
+      })
+      .collect::<Vec<_>>();
+    renderer.draw_masked(&things);
     *data.7 = view_finder.result;
   }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use crate::angle::Angle;
+  use crate::component::*;
+  use crate::render::camera::Camera;
+  use crate::render::flat::Flats;
+  use crate::render::frame_buffer::FrameBuffer;
+  use crate::render::lighting::Lighting;
+  use crate::render::placeholder_color;
+  use crate::render::sprite::Sprites;
+  use crate::render::texture::{Sky, Texture, Textures};
+  use crate::system::view::{SolidSegs, View, ViewFinder};
+  use crate::wad::colormap::ColorMap;
+  use crate::wad::linedef::{LineDef, LOWER_UNPEGGED, NO_SIDE_DEF, UPPER_UNPEGGED};
+  use crate::wad::node::{BoundingBox, Node};
+  use crate::wad::picture::{Picture, Post};
+  use crate::wad::sector::Sector;
+  use crate::wad::seg::Seg;
+  use crate::wad::sidedef::SideDef;
+  use crate::wad::ssector::SSector;
+  use crate::wad::vertex::{Vertex, VisibleVertexes};
+  use specs::prelude::*;
+
+  fn side_def(sector: u16, upper: &str, middle: &str) -> SideDef {
+    SideDef {
+      x_offset: 0,
+      y_offset: 0,
+      upper_texture: String::from(upper),
+      lower_texture: String::from("-"),
+      middle_texture: String::from(middle),
+      sector,
+    }
+  }
+
+  fn sector(floor_height: i16, ceiling_height: i16, ceiling: &str) -> Sector {
+    Sector {
+      floor_height,
+      ceiling_height,
+      floor_texture: String::from("FLOOR"),
+      ceiling_texture: String::from(ceiling),
+      light_level: 255,
+      special_type: 0,
+      tag: 0,
+    }
+  }
+
+  fn line_def(start_vertex: u16, end_vertex: u16, front: u16, back: u16) -> LineDef {
+    LineDef {
+      start_vertex,
+      end_vertex,
+      flags: if back == NO_SIDE_DEF { 1 } else { 4 },
+      line_type: 0,
+      sector_tag: 0,
+      front_side_def: front,
+      back_side_def: back,
+    }
+  }
+
+  fn seg(start_vertex: u16, end_vertex: u16, linedef: u16, side: u16) -> Seg {
+    Seg {
+      start_vertex,
+      end_vertex,
+      angle: 0,
+      linedef,
+      side,
+      offset: 0,
+    }
+  }
+
+  fn world(
+    vertexes: Vec<Vertex>,
+    line_defs: Vec<LineDef>,
+    side_defs: Vec<SideDef>,
+    sectors: Vec<Sector>,
+    segs: Vec<Seg>,
+    ssectors: Vec<SSector>,
+    nodes: Vec<Node>,
+  ) -> World {
+    let mut world = World::new();
+    world.register::<KeyboardControlled>();
+    world.register::<Position>();
+    world.register::<Rotation>();
+    world.register::<Sprite>();
+    world.insert(vertexes);
+    world.insert(line_defs);
+    world.insert(side_defs);
+    world.insert(sectors);
+    world.insert(segs);
+    world.insert(ssectors);
+    world.insert(nodes);
+    world.insert(VisibleVertexes::default());
+    world.insert(FrameBuffer::default());
+    world.insert(Textures::default());
+    world.insert(Sky::default());
+    world.insert(Flats::default());
+    world.insert(Lighting::default());
+    world.insert(Sprites::default());
+    world
+  }
+
+  /// A 1024x1024 room with a single sector and a differently textured wall
+  /// on every side, without any nodes.
+  pub(crate) fn room() -> World {
+    world(
+      vec![
+        Vertex { x: 0, y: 0 },
+        Vertex { x: 0, y: 1024 },
+        Vertex { x: 1024, y: 1024 },
+        Vertex { x: 1024, y: 0 },
+      ],
+      (0..4)
+        .map(|i| line_def(i, (i + 1) % 4, i, NO_SIDE_DEF))
+        .collect(),
+      vec![
+        side_def(0, "-", "WEST"),
+        side_def(0, "-", "NORTH"),
+        side_def(0, "-", "EAST"),
+        side_def(0, "-", "SOUTH"),
+      ],
+      vec![sector(0, 128, "CEILING")],
+      (0..4).map(|i| seg(i, (i + 1) % 4, i, 0)).collect(),
+      vec![SSector {
+        seg_count: 4,
+        first_seg: 0,
+      }],
+      Vec::new(),
+    )
+  }
+
+  /// The same room split at x = 512 by a two sided line. The east half has a
+  /// lower ceiling.
+  pub(crate) fn two_rooms() -> World {
+    world(
+      vec![
+        Vertex { x: 0, y: 0 },
+        Vertex { x: 0, y: 1024 },
+        Vertex { x: 512, y: 1024 },
+        Vertex { x: 1024, y: 1024 },
+        Vertex { x: 1024, y: 0 },
+        Vertex { x: 512, y: 0 },
+      ],
+      vec![
+        line_def(0, 1, 0, NO_SIDE_DEF),
+        line_def(1, 2, 1, NO_SIDE_DEF),
+        line_def(5, 0, 2, NO_SIDE_DEF),
+        line_def(2, 3, 3, NO_SIDE_DEF),
+        line_def(3, 4, 4, NO_SIDE_DEF),
+        line_def(4, 5, 5, NO_SIDE_DEF),
+        line_def(5, 2, 6, 7),
+      ],
+      vec![
+        side_def(0, "-", "WEST"),
+        side_def(0, "-", "NORTH"),
+        side_def(0, "-", "SOUTH"),
+        side_def(1, "-", "NORTH"),
+        side_def(1, "-", "EAST"),
+        side_def(1, "-", "SOUTH"),
+        side_def(1, "-", "-"),
+        side_def(0, "STEP", "-"),
+      ],
+      vec![sector(0, 128, "CEILING"), sector(0, 64, "LOW_CEILING")],
+      vec![
+        seg(0, 1, 0, 0),
+        seg(1, 2, 1, 0),
+        seg(2, 5, 6, 1),
+        seg(5, 0, 2, 0),
+        seg(2, 3, 3, 0),
+        seg(3, 4, 4, 0),
+        seg(4, 5, 5, 0),
+        seg(5, 2, 6, 0),
+      ],
+      vec![
+        SSector {
+          seg_count: 4,
+          first_seg: 0,
+        },
+        SSector {
+          seg_count: 4,
+          first_seg: 4,
+        },
+      ],
+      vec![Node {
+        x_partition: 512,
+        y_partition: 0,
+        change_x_partition: 0,
+        change_y_partition: 1024,
+        right_box_top: 1024,
+        right_box_bottom: 0,
+        right_box_left: 512,
+        right_box_right: 1024,
+        left_box_top: 1024,
+        left_box_bottom: 0,
+        left_box_left: 0,
+        left_box_right: 512,
+        right_child: 0x8001,
+        left_child: 0x8000,
+      }],
+    )
+  }
+
+  pub(crate) fn look(world: &mut World, x: i16, y: i16, angle: f32) {
+    world
+      .create_entity()
+      .with(KeyboardControlled)
+      .with(Position { x, y })
+      .with(Rotation {
+        speed: 0.0,
+        angle: Angle::new(angle),
+        fov: 90,
+      })
+      .build();
+    View {}.run_now(world);
+  }
+
+  pub(crate) fn pixel(world: &World, x: usize, y: usize) -> u8 {
+    let frame_buffer = world.read_resource::<FrameBuffer>();
+    frame_buffer.pixels[y * frame_buffer.width + x]
+  }
+
+  #[test]
+  fn render_walls_flats_and_ceilings() {
+    let mut world = room();
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 10), placeholder_color("CEILING"));
+    assert_eq!(pixel(&world, 160, 100), placeholder_color("EAST"));
+    assert_eq!(pixel(&world, 160, 190), placeholder_color("FLOOR"));
+  }
+
+  #[test]
+  fn render_walls_on_both_sides() {
+    let mut world = room();
+    look(&mut world, 512, 512, 45.0);
+    assert_eq!(pixel(&world, 100, 100), placeholder_color("NORTH"));
+    assert_eq!(pixel(&world, 220, 100), placeholder_color("EAST"));
+  }
+
+  #[test]
+  fn render_through_two_sided_lines() {
+    let mut world = two_rooms();
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 20), placeholder_color("CEILING"));
+    assert_eq!(pixel(&world, 160, 60), placeholder_color("STEP"));
+    assert_eq!(pixel(&world, 160, 90), placeholder_color("LOW_CEILING"));
+    assert_eq!(pixel(&world, 160, 100), placeholder_color("EAST"));
+    assert_eq!(pixel(&world, 160, 190), placeholder_color("FLOOR"));
+  }
+
+  fn texture(width: usize, height: usize, texel: impl Fn(usize, usize) -> u8) -> Texture {
+    let mut pixels = Vec::with_capacity(width * height);
+    for x in 0..width {
+      for y in 0..height {
+        pixels.push(texel(x, y));
+      }
+    }
+    Texture::new(width, height, pixels)
+  }
+
+  #[test]
+  fn render_textured_walls() {
+    let mut world = room();
+    world
+      .write_resource::<Textures>()
+      .insert("EAST", texture(256, 100, |x, _| x as u8));
+    world.write_resource::<Vec<SideDef>>()[2].x_offset = 10;
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 100), 11);
+  }
+
+  #[test]
+  fn render_pegged_middle_textures() {
+    let texture = || texture(4, 100, |_, y| y as u8);
+    let mut world = room();
+    world.write_resource::<Textures>().insert("EAST", texture());
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 100), 88);
+
+    let mut world = room();
+    world.write_resource::<Textures>().insert("EAST", texture());
+    world.write_resource::<Vec<LineDef>>()[2].flags |= LOWER_UNPEGGED;
+    world.write_resource::<Vec<SideDef>>()[2].y_offset = 5;
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 100), 65);
+  }
+
+  #[test]
+  fn render_pegged_upper_textures() {
+    let texture = || texture(4, 100, |_, y| y as u8);
+    let mut world = two_rooms();
+    world.write_resource::<Textures>().insert("STEP", texture());
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 60), 59);
+
+    let mut world = two_rooms();
+    world.write_resource::<Textures>().insert("STEP", texture());
+    world.write_resource::<Vec<LineDef>>()[6].flags |= UPPER_UNPEGGED;
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 60), 23);
+  }
+
+  #[test]
+  fn render_flats_as_spans() {
+    let flat = || (0..64 * 64).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let mut world = room();
+    world.write_resource::<Flats>().insert("FLOOR", flat());
+    world.write_resource::<Flats>().insert("CEILING", flat());
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 190), 8);
+    assert_eq!(pixel(&world, 160, 10), 27);
+  }
+
+  #[test]
+  fn render_sky_panning_with_the_player() {
+    let sky = || texture(256, 128, |x, _| x as u8);
+    let mut world = room();
+    world.write_resource::<Vec<Sector>>()[0].ceiling_texture = String::from("F_SKY1");
+    world.write_resource::<Textures>().insert("SKY1", sky());
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 10), 255);
+    assert_eq!(pixel(&world, 159, 10), 0);
+
+    let mut world = room();
+    world.write_resource::<Vec<Sector>>()[0].ceiling_texture = String::from("F_SKY1");
+    world.write_resource::<Textures>().insert("SKY1", sky());
+    look(&mut world, 512, 512, 45.0);
+    assert_eq!(pixel(&world, 160, 10), 127);
+  }
+
+  #[test]
+  fn render_the_sky_of_the_map() {
+    let mut world = room();
+    world.write_resource::<Vec<Sector>>()[0].ceiling_texture = String::from("F_SKY1");
+    world
+      .write_resource::<Textures>()
+      .insert("SKY1", texture(256, 128, |_, _| 1));
+    world
+      .write_resource::<Textures>()
+      .insert("SKY3", texture(256, 128, |_, _| 3));
+    world.insert(Sky::new("MAP25"));
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 10), 3);
+  }
+
+  #[test]
+  fn render_with_sector_light_and_distance() {
+    // Every colormap maps all colors onto its own index.
+    let lump = (0..34 * 256).map(|i| (i / 256) as u8).collect::<Vec<_>>();
+    let mut world = room();
+    world.write_resource::<Vec<Sector>>()[0].light_level = 160;
+    world.insert(Lighting::new(ColorMap::new(&lump).unwrap()));
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 100), 14);
+    assert_eq!(pixel(&world, 160, 190), 3);
+
+    world.write_resource::<Lighting>().set_invulnerable(true);
+    View {}.run_now(&world);
+    assert_eq!(pixel(&world, 160, 100), 32);
+    assert_eq!(pixel(&world, 160, 190), 32);
+  }
+
+  // Places a solid sprite, `height` units tall and 8 wide, centered on its
+  // position.
+  fn place_sprite(world: &mut World, x: i16, y: i16, height: usize) {
+    world
+      .write_resource::<Sprites>()
+      .insert(
+        "TESTA0",
+        Picture {
+          width: 8,
+          height,
+          left_offset: 4,
+          top_offset: height as i16,
+          columns: vec![
+            vec![Post {
+              top_delta: 0,
+              pixels: vec![77; height],
+            }];
+            8
+          ],
+        },
+      )
+      .unwrap();
+    world
+      .create_entity()
+      .with(Position { x, y })
+      .with(Sprite {
+        name: String::from("TEST"),
+        frame: 'A',
+        angle: Angle::new(0.0),
+      })
+      .build();
+  }
+
+  #[test]
+  fn render_sprites() {
+    let mut world = room();
+    place_sprite(&mut world, 768, 512, 16);
+    look(&mut world, 512, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 120), 77);
+    assert_eq!(pixel(&world, 158, 120), 77);
+    assert_eq!(pixel(&world, 157, 120), placeholder_color("FLOOR"));
+    assert_eq!(pixel(&world, 160, 110), placeholder_color("EAST"));
+  }
+
+  #[test]
+  fn clip_sprites_behind_walls() {
+    let mut world = two_rooms();
+    place_sprite(&mut world, 768, 512, 128);
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 80), placeholder_color("STEP"));
+    assert_eq!(pixel(&world, 160, 100), 77);
+
+    // Behind the player.
+    let mut world = two_rooms();
+    place_sprite(&mut world, 128, 512, 128);
+    look(&mut world, 256, 512, 0.0);
+    assert_ne!(pixel(&world, 160, 100), 77);
+  }
+
+  // A see through middle texture on the line between the two rooms.
+  fn place_grate(world: &mut World) {
+    let mut grate = texture(4, 128, |_, _| 99);
+    grate.opaque = vec![vec![(0, 10), (20, 30)]; 4];
+    world.write_resource::<Textures>().insert("GRATE", grate);
+    world.write_resource::<Vec<SideDef>>()[7].middle_texture = String::from("GRATE");
+  }
+
+  #[test]
+  fn render_masked_middle_textures() {
+    let mut world = two_rooms();
+    place_grate(&mut world);
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 88), 99);
+    assert_eq!(pixel(&world, 160, 96), placeholder_color("EAST"));
+    assert_eq!(pixel(&world, 160, 100), 99);
+    assert_eq!(pixel(&world, 160, 106), placeholder_color("EAST"));
+  }
+
+  #[test]
+  fn render_masked_middle_textures_and_sprites() {
+    let mut world = two_rooms();
+    place_grate(&mut world);
+    place_sprite(&mut world, 768, 512, 128);
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 88), 99);
+    assert_eq!(pixel(&world, 160, 96), 77);
+
+    let mut world = two_rooms();
+    place_grate(&mut world);
+    place_sprite(&mut world, 384, 512, 128);
+    look(&mut world, 256, 512, 0.0);
+    assert_eq!(pixel(&world, 160, 88), 77);
+    assert_eq!(pixel(&world, 160, 100), 77);
+  }
+
+  #[test]
+  fn clip_solid_segs() {
+    let mut solid_segs = SolidSegs::new(320);
+    assert_eq!(solid_segs.clip(100, 200, true), vec![(100, 200)]);
+    assert_eq!(solid_segs.clip(50, 150, false), vec![(50, 100)]);
+    assert_eq!(solid_segs.clip(120, 180, true), vec![]);
+    assert_eq!(solid_segs.clip(0, 320, true), vec![(0, 100), (200, 320)]);
+    assert!(solid_segs.is_full());
+    assert_eq!(solid_segs.clip(10, 20, true), vec![]);
+  }
+
+  #[test]
+  fn clip_segs_between_solid_ranges() {
+    let mut solid_segs = SolidSegs::new(320);
+    solid_segs.clip(0, 50, true);
+    solid_segs.clip(100, 150, true);
+    assert_eq!(solid_segs.clip(25, 200, false), vec![(50, 100), (150, 200)]);
+    assert_eq!(solid_segs.clip(50, 100, true), vec![(50, 100)]);
+    assert_eq!(solid_segs.ranges, vec![(0, 150)]);
+    assert!(!solid_segs.is_full());
+  }
+
+  // Runs `f` with a view finder for a player at the origin looking east.
+  fn with_view_finder<T>(f: impl FnOnce(&mut ViewFinder) -> T) -> T {
+    let rotation = Rotation {
+      speed: 0.0,
+      angle: Angle::new(0.0),
+      fov: 90,
+    };
+    let position = Position { x: 0, y: 0 };
+    let (nodes, segs, ssectros, vertexes) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let (line_defs, side_defs, sectors) = (Vec::new(), Vec::new(), Vec::new());
+    let mut view_finder = ViewFinder {
+      nodes: &nodes,
+      segs: &segs,
+      ssectros: &ssectros,
+      vertexes: &vertexes,
+      line_defs: &line_defs,
+      side_defs: &side_defs,
+      sectors: &sectors,
+      player: (&rotation, &position, &KeyboardControlled),
+      camera: Camera {
+        x: 0.0,
+        y: 0.0,
+        z: 41.0,
+        angle: 0.0,
+        fov: 90.0,
+        width: 320,
+        height: 200,
+      },
+      solid_segs: SolidSegs::new(320),
+      result: Default::default(),
+      visible_segs: Vec::new(),
+    };
+    f(&mut view_finder)
+  }
+
+  fn check_bbox(bbox: BoundingBox, covered: Option<(usize, usize)>) -> bool {
+    with_view_finder(|view_finder| {
+      if let Some((x1, x2)) = covered {
+        view_finder.solid_segs.clip(x1, x2, true);
+      }
+      view_finder.check_bbox(bbox)
+    })
+  }
+
+  fn clip(v1: (i16, i16), v2: (i16, i16)) -> Option<(f32, f32, usize, usize)> {
+    let v1 = Vertex { x: v1.0, y: v1.1 };
+    let v2 = Vertex { x: v2.0, y: v2.1 };
+    with_view_finder(|view_finder| view_finder.clip_vertexes_in_fov(&v1, &v2)).map(|clipped| {
+      (
+        clipped.left.round(),
+        clipped.right.round(),
+        clipped.x1,
+        clipped.x2,
+      )
+    })
+  }
+
+  #[test]
+  fn clip_segs_to_fov() {
+    assert_eq!(clip((100, 50), (100, -50)), Some((27.0, -27.0, 80, 240)));
+    assert_eq!(clip((100, 200), (100, -50)), Some((45.0, -27.0, 0, 240)));
+    assert_eq!(clip((100, 50), (100, -200)), Some((27.0, -45.0, 80, 320)));
+    assert_eq!(clip((100, 500), (100, -500)), Some((45.0, -45.0, 0, 320)));
+    assert_eq!(clip((-100, 100), (100, -10)), Some((45.0, -6.0, 0, 176)));
+  }
+
+  #[test]
+  fn clip_segs_outside_of_fov() {
+    // Facing away from the player.
+    assert_eq!(clip((100, -50), (100, 50)), None);
+    // Behind the player.
+    assert_eq!(clip((-100, -50), (-100, 50)), None);
+    // Left and right of the view.
+    assert_eq!(clip((-100, 100), (100, 100)), None);
+    assert_eq!(clip((100, -100), (-100, -100)), None);
+  }
+
+  #[test]
+  fn report_clipped_vertexes_to_the_automap() {
+    let mut world = room();
+    look(&mut world, 768, 512, 0.0);
+    let visible_vertexes = world.read_resource::<VisibleVertexes>();
+    let vertexes = visible_vertexes
+      .vertexes
+      .iter()
+      .map(|(v1, v2)| ((v1.x, v1.y), (v2.x, v2.y)))
+      .collect::<Vec<_>>();
+    assert_eq!(vertexes, vec![((1024, 768), (1024, 256))]);
+  }
+
+  fn bbox(left: i16, bottom: i16, right: i16, top: i16) -> BoundingBox {
+    BoundingBox {
+      top,
+      bottom,
+      left,
+      right,
+    }
+  }
+
+  #[test]
+  fn check_bbox_against_fov() {
+    assert!(check_bbox(bbox(100, -50, 200, 50), None));
+    assert!(check_bbox(bbox(-50, 20, 100, 100), None));
+    assert!(check_bbox(bbox(-100, -100, 100, 100), None));
+    assert!(!check_bbox(bbox(-200, -50, -100, 50), None));
+    assert!(!check_bbox(bbox(-100, 100, 50, 200), None));
+  }
+
+  #[test]
+  fn check_bbox_against_solid_segs() {
+    assert!(!check_bbox(bbox(100, -50, 200, 50), Some((0, 320))));
+    assert!(!check_bbox(bbox(100, -50, 200, 50), Some((60, 260))));
+    assert!(check_bbox(bbox(100, -50, 200, 50), Some((0, 160))));
+    assert!(check_bbox(bbox(-100, -100, 100, 100), Some((0, 320))));
+  }
+
+  #[test]
+  fn cull_nodes_behind_the_player() {
+    let mut world = two_rooms();
+    look(&mut world, 256, 512, 180.0);
+    let visible_vertexes = world.read_resource::<VisibleVertexes>();
+    assert!(visible_vertexes
+      .vertexes
+      .iter()
+      .all(|(v1, v2)| v1.x <= 512 && v2.x <= 512));
+  }
+}
@@ -1,4 +1,5 @@
 use crate::datatypes::Result;
+use crate::errors::DoomError;
 use js_sys::Uint8Array;
 use std::convert::TryFrom;
 use wasm_bindgen::JsValue;
@@ -21,20 +22,38 @@ pub fn to_vec_u8(js: &JsValue) -> Vec<u8> {
   vec
 }
 
+pub fn to_slice(array: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+  offset
+    .checked_add(len)
+    .and_then(|end| array.get(offset..end))
+    .ok_or(DoomError::Truncated {
+      lump: None,
+      offset,
+      end: offset.saturating_add(len),
+      actual: array.len(),
+    })
+}
+
 pub fn to_i16(array: &[u8], offset: usize) -> Result<i16> {
-  Ok(i16::from_le_bytes(<[u8; 2]>::try_from(
-    &array[offset..offset + 2],
-  )?))
+  Ok(i16::from_le_bytes(<[u8; 2]>::try_from(to_slice(
+    array, offset, 2,
+  )?)?))
 }
 
 pub fn to_u16(array: &[u8], offset: usize) -> Result<u16> {
-  Ok(u16::from_le_bytes(<[u8; 2]>::try_from(
-    &array[offset..offset + 2],
-  )?))
+  Ok(u16::from_le_bytes(<[u8; 2]>::try_from(to_slice(
+    array, offset, 2,
+  )?)?))
 }
 
 pub fn to_u32(array: &[u8], offset: usize) -> Result<u32> {
-  Ok(u32::from_le_bytes(<[u8; 4]>::try_from(
-    &array[offset..offset + 4],
-  )?))
+  Ok(u32::from_le_bytes(<[u8; 4]>::try_from(to_slice(
+    array, offset, 4,
+  )?)?))
+}
+
+pub fn to_name(array: &[u8], offset: usize) -> Result<String> {
+  let bytes = to_slice(array, offset, 8)?;
+  let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+  Ok(String::from_utf8(Vec::from(&bytes[..len]))?)
 }
@@ -0,0 +1,486 @@
+use crate::wad::linedef::{LineDef, NO_SIDE_DEF};
+use crate::wad::node::Node;
+use crate::wad::sector::Sector;
+use crate::wad::seg::Seg;
+use crate::wad::sidedef::SideDef;
+use crate::wad::ssector::SSector;
+use crate::wad::thing::Thing;
+use crate::wad::vertex::Vertex;
+use crate::SUB_SECTOR_IDENTIFIER;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const PLAYER_ONE_START: u16 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+  /// The map can't be used, e.g. the BSP walk would index out of bounds.
+  Error,
+  /// The map is broken but can still be loaded.
+  Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+  /// Fail on every diagnostic.
+  Strict,
+  /// Only fail on errors and keep the warnings.
+  Lenient,
+}
+
+impl ValidationMode {
+  pub fn rejects(self, diagnostic: &Diagnostic) -> bool {
+    match self {
+      ValidationMode::Strict => true,
+      ValidationMode::Lenient => diagnostic.severity == Severity::Error,
+    }
+  }
+}
+
+pub struct Validator<'a> {
+  pub things: &'a [Thing],
+  pub line_defs: &'a [LineDef],
+  pub side_defs: &'a [SideDef],
+  pub vertexes: &'a [Vertex],
+  pub segs: &'a [Seg],
+  pub ssectors: &'a [SSector],
+  pub nodes: &'a [Node],
+  pub sectors: &'a [Sector],
+}
+
+impl<'a> Validator<'a> {
+  pub fn validate(&self) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    self.check_line_defs(&mut diagnostics);
+    self.check_side_defs(&mut diagnostics);
+    self.check_segs(&mut diagnostics);
+    self.check_ssectors(&mut diagnostics);
+    self.check_nodes(&mut diagnostics);
+    self.check_sectors(&mut diagnostics);
+    self.check_things(&mut diagnostics);
+    diagnostics
+  }
+
+  fn error(diagnostics: &mut Vec<Diagnostic>, message: String) {
+    diagnostics.push(Diagnostic {
+      severity: Severity::Error,
+      message,
+    });
+  }
+
+  fn warning(diagnostics: &mut Vec<Diagnostic>, message: String) {
+    diagnostics.push(Diagnostic {
+      severity: Severity::Warning,
+      message,
+    });
+  }
+
+  fn check_range(
+    diagnostics: &mut Vec<Diagnostic>,
+    owner: &str,
+    id: usize,
+    kind: &str,
+    index: u16,
+    max: usize,
+  ) -> bool {
+    if usize::from(index) >= max {
+      Validator::error(
+        diagnostics,
+        format!(
+          "{} {} references {} {} but there are only {}",
+          owner, id, kind, index, max
+        ),
+      );
+      return false;
+    }
+    true
+  }
+
+  fn check_line_defs(&self, diagnostics: &mut Vec<Diagnostic>) {
+    let vertex_count = self.vertexes.len();
+    let side_def_count = self.side_defs.len();
+    for (id, line_def) in self.line_defs.iter().enumerate() {
+      let start = Validator::check_range(
+        diagnostics,
+        "LineDef",
+        id,
+        "vertex",
+        line_def.start_vertex,
+        vertex_count,
+      );
+      let end = Validator::check_range(
+        diagnostics,
+        "LineDef",
+        id,
+        "vertex",
+        line_def.end_vertex,
+        vertex_count,
+      );
+      if start && end {
+        let v1 = &self.vertexes[line_def.start_vertex as usize];
+        let v2 = &self.vertexes[line_def.end_vertex as usize];
+        if v1.x == v2.x && v1.y == v2.y {
+          Validator::warning(diagnostics, format!("LineDef {} has zero length", id));
+        }
+      }
+
+      if line_def.front_side_def == NO_SIDE_DEF {
+        Validator::error(diagnostics, format!("LineDef {} has no front side", id));
+      } else {
+        Validator::check_range(
+          diagnostics,
+          "LineDef",
+          id,
+          "sidedef",
+          line_def.front_side_def,
+          side_def_count,
+        );
+      }
+      if line_def.back_side_def != NO_SIDE_DEF {
+        Validator::check_range(
+          diagnostics,
+          "LineDef",
+          id,
+          "sidedef",
+          line_def.back_side_def,
+          side_def_count,
+        );
+      }
+    }
+  }
+
+  fn check_side_defs(&self, diagnostics: &mut Vec<Diagnostic>) {
+    for (id, side_def) in self.side_defs.iter().enumerate() {
+      Validator::check_range(
+        diagnostics,
+        "SideDef",
+        id,
+        "sector",
+        side_def.sector,
+        self.sectors.len(),
+      );
+    }
+  }
+
+  fn check_segs(&self, diagnostics: &mut Vec<Diagnostic>) {
+    let vertex_count = self.vertexes.len();
+    for (id, seg) in self.segs.iter().enumerate() {
+      Validator::check_range(
+        diagnostics,
+        "Seg",
+        id,
+        "vertex",
+        seg.start_vertex,
+        vertex_count,
+      );
+      Validator::check_range(
+        diagnostics,
+        "Seg",
+        id,
+        "vertex",
+        seg.end_vertex,
+        vertex_count,
+      );
+      if !Validator::check_range(
+        diagnostics,
+        "Seg",
+        id,
+        "linedef",
+        seg.linedef,
+        self.line_defs.len(),
+      ) {
+        continue;
+      }
+
+      let line_def = &self.line_defs[seg.linedef as usize];
+      match seg.side {
+        0 => (),
+        1 if line_def.back_side_def != NO_SIDE_DEF => (),
+        1 => Validator::error(
+          diagnostics,
+          format!(
+            "Seg {} is on the back of one sided LineDef {}",
+            id, seg.linedef
+          ),
+        ),
+        side => Validator::error(diagnostics, format!("Seg {} has invalid side {}", id, side)),
+      }
+    }
+  }
+
+  fn check_ssectors(&self, diagnostics: &mut Vec<Diagnostic>) {
+    for (id, ssector) in self.ssectors.iter().enumerate() {
+      let last_seg = usize::from(ssector.first_seg) + usize::from(ssector.seg_count);
+      if ssector.seg_count == 0 {
+        Validator::error(diagnostics, format!("SSector {} has no segs", id));
+      } else if last_seg > self.segs.len() {
+        Validator::error(
+          diagnostics,
+          format!(
+            "SSector {} references segs {}..{} but there are only {}",
+            id,
+            ssector.first_seg,
+            last_seg,
+            self.segs.len()
+          ),
+        );
+      }
+    }
+  }
+
+  fn check_nodes(&self, diagnostics: &mut Vec<Diagnostic>) {
+    if self.nodes.is_empty() && self.ssectors.len() > 1 {
+      Validator::error(
+        diagnostics,
+        format!("There are no nodes for {} SSectors", self.ssectors.len()),
+      );
+    }
+
+    for (id, node) in self.nodes.iter().enumerate() {
+      for &child in &[node.right_child, node.left_child] {
+        let child = usize::from(child);
+        if child & SUB_SECTOR_IDENTIFIER > 0 {
+          let ssector = child & !SUB_SECTOR_IDENTIFIER;
+          if ssector >= self.ssectors.len() {
+            Validator::error(
+              diagnostics,
+              format!(
+                "Node {} references SSector {} but there are only {}",
+                id,
+                ssector,
+                self.ssectors.len()
+              ),
+            );
+          }
+        } else if child >= id {
+          // Node builders always store children in front of their parents,
+          // anything else would send the BSP walk into a loop.
+          Validator::error(
+            diagnostics,
+            format!("Node {} references Node {} which isn't below it", id, child),
+          );
+        }
+      }
+    }
+  }
+
+  // In a closed sector every vertex is shared by an even number of its lines.
+  fn check_sectors(&self, diagnostics: &mut Vec<Diagnostic>) {
+    let mut sector_vertexes: HashMap<u16, HashMap<u16, usize>> = HashMap::new();
+    for line_def in self.line_defs {
+      for &side_def in &[line_def.front_side_def, line_def.back_side_def] {
+        let sector = match self.side_defs.get(usize::from(side_def)) {
+          Some(side_def) => side_def.sector,
+          None => continue,
+        };
+        let vertexes = sector_vertexes.entry(sector).or_default();
+        *vertexes.entry(line_def.start_vertex).or_default() += 1;
+        *vertexes.entry(line_def.end_vertex).or_default() += 1;
+      }
+    }
+
+    let mut sectors: Vec<_> = sector_vertexes.into_iter().collect();
+    sectors.sort_unstable_by_key(|(sector, _)| *sector);
+    for (sector, vertexes) in sectors {
+      let mut open: Vec<_> = vertexes
+        .into_iter()
+        .filter(|(_, count)| count % 2 == 1)
+        .map(|(vertex, _)| vertex)
+        .collect();
+      if !open.is_empty() {
+        open.sort_unstable();
+        Validator::warning(
+          diagnostics,
+          format!("Sector {} is not closed at vertexes {:?}", sector, open),
+        );
+      }
+    }
+  }
+
+  fn check_things(&self, diagnostics: &mut Vec<Diagnostic>) {
+    if !self
+      .things
+      .iter()
+      .any(|thing| thing.typ == PLAYER_ONE_START)
+    {
+      Validator::warning(diagnostics, String::from("There is no player 1 start"));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::validation::{Severity, ValidationMode, Validator};
+  use crate::wad::linedef::LineDef;
+  use crate::wad::node::Node;
+  use crate::wad::sector::Sector;
+  use crate::wad::seg::Seg;
+  use crate::wad::sidedef::SideDef;
+  use crate::wad::ssector::SSector;
+  use crate::wad::thing::Thing;
+  use crate::wad::vertex::Vertex;
+
+  struct Map {
+    things: Vec<Thing>,
+    line_defs: Vec<LineDef>,
+    side_defs: Vec<SideDef>,
+    vertexes: Vec<Vertex>,
+    segs: Vec<Seg>,
+    ssectors: Vec<SSector>,
+    nodes: Vec<Node>,
+    sectors: Vec<Sector>,
+  }
+
+  impl Map {
+    fn validator(&self) -> Validator<'_> {
+      Validator {
+        things: &self.things,
+        line_defs: &self.line_defs,
+        side_defs: &self.side_defs,
+        vertexes: &self.vertexes,
+        segs: &self.segs,
+        ssectors: &self.ssectors,
+        nodes: &self.nodes,
+        sectors: &self.sectors,
+      }
+    }
+  }
+
+  fn line_def(start_vertex: u16, end_vertex: u16) -> LineDef {
+    LineDef {
+      start_vertex,
+      end_vertex,
+      flags: 1,
+      line_type: 0,
+      sector_tag: 0,
+      front_side_def: 0,
+      back_side_def: 0xFFFF,
+    }
+  }
+
+  fn seg(start_vertex: u16, end_vertex: u16, linedef: u16) -> Seg {
+    Seg {
+      start_vertex,
+      end_vertex,
+      angle: 0,
+      linedef,
+      side: 0,
+      offset: 0,
+    }
+  }
+
+  // A single square sector in one SSector.
+  fn square() -> Map {
+    Map {
+      things: vec![Thing {
+        x: 32,
+        y: 32,
+        direction: 0,
+        typ: 1,
+        flags: 7,
+      }],
+      line_defs: vec![
+        line_def(0, 1),
+        line_def(1, 2),
+        line_def(2, 3),
+        line_def(3, 0),
+      ],
+      side_defs: vec![SideDef {
+        x_offset: 0,
+        y_offset: 0,
+        upper_texture: String::from("-"),
+        lower_texture: String::from("-"),
+        middle_texture: String::from("STARTAN3"),
+        sector: 0,
+      }],
+      vertexes: vec![
+        Vertex { x: 0, y: 0 },
+        Vertex { x: 0, y: 64 },
+        Vertex { x: 64, y: 64 },
+        Vertex { x: 64, y: 0 },
+      ],
+      segs: vec![seg(0, 1, 0), seg(1, 2, 1), seg(2, 3, 2), seg(3, 0, 3)],
+      ssectors: vec![SSector {
+        seg_count: 4,
+        first_seg: 0,
+      }],
+      nodes: vec![],
+      sectors: vec![Sector {
+        floor_height: 0,
+        ceiling_height: 128,
+        floor_texture: String::from("FLOOR4_8"),
+        ceiling_texture: String::from("CEIL3_5"),
+        light_level: 160,
+        special_type: 0,
+        tag: 0,
+      }],
+    }
+  }
+
+  #[test]
+  fn valid_map() {
+    assert!(square().validator().validate().is_empty());
+  }
+
+  #[test]
+  fn dangling_references() {
+    let mut map = square();
+    map.line_defs[0].end_vertex = 9;
+    map.side_defs[0].sector = 1;
+    map.segs[1].linedef = 4;
+    map.ssectors[0].seg_count = 5;
+    let diagnostics = map.validator().validate();
+    let errors = diagnostics
+      .iter()
+      .filter(|diagnostic| diagnostic.severity == Severity::Error)
+      .count();
+    assert_eq!(errors, 4);
+  }
+
+  #[test]
+  fn node_loops() {
+    let mut map = square();
+    map.nodes.push(Node {
+      x_partition: 0,
+      y_partition: 0,
+      change_x_partition: 0,
+      change_y_partition: 64,
+      right_box_top: 64,
+      right_box_bottom: 0,
+      right_box_left: 0,
+      right_box_right: 64,
+      left_box_top: 64,
+      left_box_bottom: 0,
+      left_box_left: 0,
+      left_box_right: 64,
+      right_child: 0x8000,
+      left_child: 0,
+    });
+    let diagnostics = map.validator().validate();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("Node 0"));
+  }
+
+  #[test]
+  fn warnings() {
+    let mut map = square();
+    map.line_defs.pop();
+    map.segs.pop();
+    map.ssectors[0].seg_count = 3;
+    map.vertexes[1] = map.vertexes[0];
+    map.things[0].typ = 2;
+    let diagnostics = map.validator().validate();
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics
+      .iter()
+      .all(|diagnostic| diagnostic.severity == Severity::Warning
+        && !ValidationMode::Lenient.rejects(diagnostic)
+        && ValidationMode::Strict.rejects(diagnostic)));
+  }
+}
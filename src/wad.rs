@@ -1,18 +1,38 @@
-pub(crate) mod linedef;
-pub(crate) mod node;
-pub(crate) mod seg;
-pub(crate) mod ssector;
-pub(crate) mod thing;
-pub(crate) mod vertex;
+pub mod animated;
+pub mod blockmap;
+pub mod colormap;
+pub mod linedef;
+pub mod node;
+pub mod picture;
+pub mod playpal;
+pub mod pnames;
+pub mod reject;
+pub mod sector;
+pub mod seg;
+pub mod sidedef;
+pub mod ssector;
+pub mod texture;
+pub mod thing;
+pub mod vertex;
 
 use crate::datatypes::Result;
 use crate::errors::DoomError;
 use crate::utils;
+use crate::wad::picture::Picture;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
 pub trait WadMetaData: Sized {
   fn read(wad: &[u8], offset: usize) -> Result<Self>;
   fn lump_name() -> String;
-  fn size_in_bytes() -> u32;
+  /// The exact size of one record on disk.
+  fn size_in_bytes() -> usize;
+  fn index() -> usize;
+}
+
+pub trait WadLump: Sized {
+  fn read(lump: &[u8]) -> Result<Self>;
+  fn lump_name() -> String;
   fn index() -> usize;
 }
 
@@ -23,38 +43,116 @@ pub struct Header {
   pub directory_offset: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+  Global,
+  Sprites,
+  Flats,
+  Patches,
+}
+
+impl Namespace {
+  // Returns the namespace a marker lump opens or closes. Sub markers like
+  // F1_START don't change the namespace and are only reported as markers.
+  fn from_marker(lump_name: &str) -> Option<(Namespace, bool)> {
+    match lump_name {
+      "S_START" | "SS_START" => Some((Namespace::Sprites, true)),
+      "S_END" | "SS_END" => Some((Namespace::Sprites, false)),
+      "F_START" | "FF_START" => Some((Namespace::Flats, true)),
+      "F_END" | "FF_END" => Some((Namespace::Flats, false)),
+      "P_START" | "PP_START" => Some((Namespace::Patches, true)),
+      "P_END" | "PP_END" => Some((Namespace::Patches, false)),
+      _ => None,
+    }
+  }
+
+  fn is_marker(lump_name: &str) -> bool {
+    Namespace::from_marker(lump_name).is_some()
+      || [
+        "F1_START", "F1_END", "F2_START", "F2_END", "F3_START", "F3_END", "P1_START", "P1_END",
+        "P2_START", "P2_END", "P3_START", "P3_END",
+      ]
+      .contains(&lump_name)
+  }
+}
+
+#[derive(Debug, Clone)]
 pub struct Directory {
   pub lump_name: String,
   pub lump_size: u32,
   pub lump_offset: u32,
+  pub namespace: Namespace,
+  pub wad_index: usize,
 }
 
+/// An IWAD with any number of PWADs stacked on top. Lumps of later files
+/// override lumps with the same name (and namespace) of earlier ones.
+#[derive(Clone)]
 pub struct Wad {
-  wad: Vec<u8>,
+  wads: Vec<Vec<u8>>,
   directories: Vec<Directory>,
 }
 
 impl Wad {
   pub fn new(wad: &[u8]) -> Result<Wad> {
+    let mut iwad = Wad {
+      wads: Vec::new(),
+      directories: Vec::new(),
+    };
+    iwad.add_wad(wad, "IWAD")?;
+    Ok(iwad)
+  }
+
+  pub fn add_pwad(&mut self, wad: &[u8]) -> Result<()> {
+    self.add_wad(wad, "PWAD")
+  }
+
+  fn add_wad(&mut self, wad: &[u8], wad_type: &str) -> Result<()> {
     let header = Wad::read_header(wad, 0)?;
+    if header.wad_type != wad_type {
+      return Err(DoomError::UnsupportedFormat(format!(
+        "Expected {} but got {}",
+        wad_type, header.wad_type
+      )));
+    }
+
+    let directory_offset = header.directory_offset as usize;
+    let directory_size = (header.directory_count as usize).saturating_mul(16);
+    if directory_offset.saturating_add(directory_size) > wad.len() {
+      return Err(DoomError::Truncated {
+        lump: Some(String::from("directory")),
+        offset: directory_offset,
+        end: directory_offset.saturating_add(directory_size),
+        actual: wad.len(),
+      });
+    }
+
+    let wad_index = self.wads.len();
     let mut directories = Vec::new();
-    for dir in 0..header.directory_count {
-      let directory = Wad::read_directory(wad, (header.directory_offset + dir * 16) as usize)?;
+    let mut namespace = Namespace::Global;
+    for dir in 0..header.directory_count as usize {
+      let mut directory = Wad::read_directory(wad, directory_offset + dir * 16)?;
+      directory.wad_index = wad_index;
+      match Namespace::from_marker(&directory.lump_name) {
+        Some((marker, true)) => namespace = marker,
+        Some((_, false)) => namespace = Namespace::Global,
+        None if !Namespace::is_marker(&directory.lump_name) => directory.namespace = namespace,
+        None => (),
+      }
       directories.push(directory);
     }
-    Ok(Wad {
-      wad: Vec::from(wad),
-      directories,
-    })
+    self.wads.push(Vec::from(wad));
+    self.directories.extend(directories);
+    Ok(())
   }
 
   fn read_header(wad: &[u8], offset: usize) -> Result<Header> {
+    // Reading the counts first makes sure the whole header is there.
+    let directory_count = utils::to_u32(wad, offset + 4).map_err(|e| e.in_lump("header"))?;
+    let directory_offset = utils::to_u32(wad, offset + 8).map_err(|e| e.in_lump("header"))?;
     let wad_type = String::from_utf8(Vec::from(&wad[offset..offset + 4]))?
       .trim_matches(char::from(0))
       .to_string();
-    let directory_count = utils::to_u32(&wad, offset + 4)?;
-    let directory_offset = utils::to_u32(&wad, offset + 8)?;
     Ok(Header {
       wad_type,
       directory_count,
@@ -63,46 +161,574 @@ impl Wad {
   }
 
   fn read_directory(wad: &[u8], offset: usize) -> Result<Directory> {
-    let lump_offset = utils::to_u32(&wad, offset)?;
-    let lump_size = utils::to_u32(&wad, offset + 4)?;
-    let lump_name = String::from_utf8(Vec::from(&wad[offset + 8..offset + 16]))?
-      .trim_matches(char::from(0))
-      .to_string();
+    let lump_offset = utils::to_u32(wad, offset)?;
+    let lump_size = utils::to_u32(wad, offset + 4)?;
+    let lump_name = utils::to_name(wad, offset + 8)?;
+
+    // Markers often have a size of 0 and a bogus offset, which is harmless.
+    let lump_end = (lump_offset as usize).saturating_add(lump_size as usize);
+    if lump_size > 0 && lump_end > wad.len() {
+      return Err(DoomError::Truncated {
+        lump: Some(lump_name),
+        offset: lump_offset as usize,
+        end: lump_end,
+        actual: wad.len(),
+      });
+    }
+
     Ok(Directory {
       lump_name,
       lump_size,
       lump_offset,
+      namespace: Namespace::Global,
+      wad_index: 0,
+    })
+  }
+
+  pub fn directories(&self) -> &[Directory] {
+    &self.directories
+  }
+
+  pub fn lumps_in(&self, namespace: Namespace) -> impl Iterator<Item = &Directory> {
+    self
+      .directories
+      .iter()
+      .filter(move |directory| directory.namespace == namespace)
+  }
+
+  /// Like the original engine, later lumps win over earlier ones with the
+  /// same name.
+  pub fn find_lump(&self, name: &str) -> Option<usize> {
+    self
+      .directories
+      .iter()
+      .rposition(|directory| directory.lump_name.eq_ignore_ascii_case(name))
+  }
+
+  pub fn find_lump_in(&self, name: &str, namespace: Namespace) -> Option<usize> {
+    self.directories.iter().rposition(|directory| {
+      directory.namespace == namespace && directory.lump_name.eq_ignore_ascii_case(name)
     })
   }
 
+  pub fn lump(&self, index: usize) -> Result<&[u8]> {
+    match self.directories.get(index) {
+      Some(directory) if directory.lump_size == 0 => Ok(&[]),
+      Some(directory) => {
+        let start = directory.lump_offset as usize;
+        let end = start + directory.lump_size as usize;
+        Ok(&self.wads[directory.wad_index][start..end])
+      }
+      None => Err(DoomError::InvalidIndex {
+        kind: "lump",
+        index,
+        max: self.directories.len(),
+      }),
+    }
+  }
+
+  pub fn read_lump(&self, name: &str) -> Result<&[u8]> {
+    match self.find_lump(name) {
+      Some(index) => self.lump(index),
+      None => Err(DoomError::LumpMissing {
+        map: None,
+        lump: String::from(name),
+      }),
+    }
+  }
+
+  pub fn read_lump_in(&self, name: &str, namespace: Namespace) -> Result<&[u8]> {
+    match self.find_lump_in(name, namespace) {
+      Some(index) => self.lump(index),
+      None => Err(DoomError::LumpMissing {
+        map: None,
+        lump: String::from(name),
+      }),
+    }
+  }
+
+  /// All flats by name in the order they are stored. Flats of later wads
+  /// replace earlier ones in place, so animations keep their frames.
+  pub fn flats(&self) -> Result<Vec<(&str, &[u8])>> {
+    let mut flats: Vec<(&str, &[u8])> = Vec::new();
+    let mut positions = HashMap::new();
+    for (index, directory) in self.directories.iter().enumerate() {
+      if directory.namespace != Namespace::Flats {
+        continue;
+      }
+      let flat = (directory.lump_name.as_str(), self.lump(index)?);
+      match positions.entry(directory.lump_name.to_uppercase()) {
+        Entry::Occupied(position) => flats[*position.get()] = flat,
+        Entry::Vacant(position) => {
+          position.insert(flats.len());
+          flats.push(flat);
+        }
+      }
+    }
+    Ok(flats)
+  }
+
+  /// Decodes a picture lump, like a menu graphic or a status bar patch.
+  pub fn read_picture(&self, name: &str) -> Result<Picture> {
+    Picture::new(self.read_lump(name)?).map_err(|e| e.in_lump(name))
+  }
+
+  pub fn read_picture_in(&self, name: &str, namespace: Namespace) -> Result<Picture> {
+    Picture::new(self.read_lump_in(name, namespace)?).map_err(|e| e.in_lump(name))
+  }
+
   pub fn find_map_index(&self, name: &str) -> Option<usize> {
-    for i in 0..self.directories.len() {
-      if self.directories[i].lump_name == name {
-        return Some(i);
+    self.find_lump(name)
+  }
+
+  fn check_map_lump(&self, map_index: usize, index: usize, lump_name: &str) -> Result<()> {
+    let map = match self.directories.get(map_index) {
+      Some(directory) => &directory.lump_name,
+      None => {
+        return Err(DoomError::InvalidIndex {
+          kind: "map",
+          index: map_index,
+          max: self.directories.len(),
+        })
       }
+    };
+    match self.directories.get(index) {
+      Some(directory) if directory.lump_name == lump_name => Ok(()),
+      _ => Err(DoomError::LumpMissing {
+        map: Some(map.clone()),
+        lump: String::from(lump_name),
+      }),
     }
-    None
+  }
+
+  pub fn read_map_lump(&self, map_index: usize, index: usize, lump_name: &str) -> Result<&[u8]> {
+    let index = map_index.saturating_add(index);
+    self.check_map_lump(map_index, index, lump_name)?;
+    self.lump(index)
+  }
+
+  pub fn read_lump_for<T: WadLump>(&self, map_index: usize) -> Result<T> {
+    T::read(self.read_map_lump(map_index, T::index(), &T::lump_name())?)
+      .map_err(|e| e.in_lump(&T::lump_name()))
   }
 
   pub fn read_wad_for<T: WadMetaData>(&self, map_index: usize) -> Result<Vec<T>> {
-    let index = map_index + T::index();
+    let lump = self.read_map_lump(map_index, T::index(), &T::lump_name())?;
 
-    if self.directories[index].lump_name != T::lump_name() {
-      return Err(DoomError::Wad(format!(
-        "Failed to load {} for MAP {}",
-        T::lump_name(),
-        self.directories[map_index].lump_name,
-      )));
+    if lump.len() % T::size_in_bytes() != 0 {
+      return Err(DoomError::LumpSizeMismatch {
+        lump: T::lump_name(),
+        size: lump.len(),
+        record_size: T::size_in_bytes(),
+      });
     }
 
     let mut vec = Vec::new();
-    for i in 0..self.directories[index].lump_size / T::size_in_bytes() {
-      let data = WadMetaData::read(
-        &self.wad,
-        (self.directories[index].lump_offset + i * T::size_in_bytes()) as usize,
-      )?;
+    for i in 0..lump.len() / T::size_in_bytes() {
+      let data =
+        WadMetaData::read(lump, i * T::size_in_bytes()).map_err(|e| e.in_lump(&T::lump_name()))?;
       vec.push(data);
     }
     Ok(vec)
   }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use crate::datatypes::{MapLumpsIndex, Result};
+  use crate::errors::DoomError;
+  use crate::wad::blockmap::Blockmap;
+  use crate::wad::linedef::LineDef;
+  use crate::wad::node::Node;
+  use crate::wad::picture::tests::picture_lump;
+  use crate::wad::reject::Reject;
+  use crate::wad::sector::Sector;
+  use crate::wad::seg::Seg;
+  use crate::wad::sidedef::SideDef;
+  use crate::wad::ssector::SSector;
+  use crate::wad::thing::Thing;
+  use crate::wad::vertex::Vertex;
+  use crate::wad::{Namespace, Wad, WadLump, WadMetaData};
+
+  pub(crate) fn build_wad(wad_type: &str, lumps: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut directory = Vec::new();
+    for (name, lump) in lumps {
+      let mut lump_name = [0u8; 8];
+      lump_name[..name.len()].copy_from_slice(name.as_bytes());
+      directory.extend_from_slice(&(12 + data.len() as u32).to_le_bytes());
+      directory.extend_from_slice(&(lump.len() as u32).to_le_bytes());
+      directory.extend_from_slice(&lump_name);
+      data.extend_from_slice(lump);
+    }
+
+    let mut wad = Vec::from(wad_type.as_bytes());
+    wad.extend_from_slice(&(lumps.len() as u32).to_le_bytes());
+    wad.extend_from_slice(&(12 + data.len() as u32).to_le_bytes());
+    wad.extend(data);
+    wad.extend(directory);
+    wad
+  }
+
+  /// Reads `lump` as the only non-empty lump of a map, in the place of the
+  /// lump `T` is read from.
+  pub(crate) fn read_map_records<T: WadMetaData>(lump: &[u8]) -> Result<Vec<T>> {
+    let names = [
+      "E1M1", "THINGS", "LINEDEFS", "SIDEDEFS", "VERTEXES", "SEGS", "SSECTORS", "NODES", "SECTORS",
+    ];
+    let mut lumps: Vec<(&str, &[u8])> = names[..T::index()]
+      .iter()
+      .map(|&name| (name, &[][..]))
+      .collect();
+    let lump_name = T::lump_name();
+    lumps.push((&lump_name, lump));
+    let wad = Wad::new(&build_wad("IWAD", &lumps))?;
+    wad.read_wad_for(wad.find_map_index("E1M1").unwrap())
+  }
+
+  #[test]
+  fn reject_partial_records() {
+    assert!(matches!(
+      read_map_records::<SideDef>(&[0; 30 + 29]),
+      Err(DoomError::LumpSizeMismatch {
+        size: 59,
+        record_size: 30,
+        ..
+      })
+    ));
+    assert!(matches!(
+      read_map_records::<Sector>(&[0; 25]),
+      Err(DoomError::LumpSizeMismatch {
+        size: 25,
+        record_size: 26,
+        ..
+      })
+    ));
+  }
+
+  fn wad() -> Wad {
+    Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("PLAYPAL", &[1, 2, 3]),
+        ("S_START", &[]),
+        ("TROOA1", &[4]),
+        ("S_END", &[]),
+        ("F_START", &[]),
+        ("F1_START", &[]),
+        ("FLOOR0_1", &[5, 6]),
+        ("F1_END", &[]),
+        ("F_END", &[]),
+        ("TROOA1", &[7]),
+      ],
+    ))
+    .unwrap()
+  }
+
+  #[test]
+  fn read_lumps_by_name() {
+    let wad = wad();
+    assert_eq!(wad.directories().len(), 10);
+    assert_eq!(wad.read_lump("PLAYPAL").unwrap(), &[1, 2, 3]);
+    assert_eq!(wad.read_lump("playpal").unwrap(), &[1, 2, 3]);
+    assert!(matches!(
+      wad.read_lump("COLORMAP"),
+      Err(DoomError::LumpMissing { map: None, .. })
+    ));
+    assert_eq!(wad.read_lump("TROOA1").unwrap(), &[7]);
+  }
+
+  #[test]
+  fn read_lumps_in_namespace() {
+    let wad = wad();
+    assert_eq!(
+      wad.read_lump_in("TROOA1", Namespace::Sprites).unwrap(),
+      &[4]
+    );
+    assert_eq!(
+      wad.read_lump_in("FLOOR0_1", Namespace::Flats).unwrap(),
+      &[5, 6]
+    );
+    assert!(wad.read_lump_in("PLAYPAL", Namespace::Flats).is_err());
+    let flats: Vec<_> = wad
+      .lumps_in(Namespace::Flats)
+      .map(|directory| directory.lump_name.as_str())
+      .collect();
+    assert_eq!(flats, vec!["FLOOR0_1"]);
+  }
+
+  #[test]
+  fn keep_lumps_named_like_sub_markers() {
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("F_START", &[]),
+        ("F1_START", &[]),
+        ("F1_LAVA", &[1]),
+        ("F1_END", &[]),
+        ("F_END", &[]),
+      ],
+    ))
+    .unwrap();
+    assert_eq!(wad.read_lump_in("F1_LAVA", Namespace::Flats).unwrap(), &[1]);
+    assert!(wad.read_lump_in("F1_START", Namespace::Flats).is_err());
+  }
+
+  #[test]
+  fn read_flats_in_order() {
+    let mut wad = wad();
+    wad
+      .add_pwad(&build_wad(
+        "PWAD",
+        &[
+          ("FF_START", &[]),
+          ("NUKAGE1", &[8]),
+          ("floor0_1", &[9]),
+          ("FF_END", &[]),
+        ],
+      ))
+      .unwrap();
+    assert_eq!(
+      wad.flats().unwrap(),
+      vec![("floor0_1", &[9][..]), ("NUKAGE1", &[8][..])]
+    );
+  }
+
+  #[test]
+  fn read_pictures_by_name() {
+    let title = picture_lump(2, &[&[(0, &[1, 2])]]);
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("TITLEPIC", &title),
+        ("M_DOOM", &title[..10]),
+        ("S_START", &[]),
+        ("TROOA1", &title),
+        ("S_END", &[]),
+      ],
+    ))
+    .unwrap();
+    let picture = wad.read_picture("titlepic").unwrap();
+    assert_eq!((picture.width, picture.height), (1, 2));
+    assert_eq!(picture.columns[0][0].pixels, vec![1, 2]);
+    assert_eq!(
+      wad.read_picture_in("TROOA1", Namespace::Sprites).unwrap(),
+      picture
+    );
+    assert!(matches!(
+      wad.read_picture("M_DOOM"),
+      Err(DoomError::Truncated { lump: Some(ref lump), .. }) if lump == "M_DOOM"
+    ));
+    assert!(matches!(
+      wad.read_picture("STBAR"),
+      Err(DoomError::LumpMissing { .. })
+    ));
+  }
+
+  #[test]
+  fn read_records() {
+    let wad = Wad::new(&build_wad(
+      "IWAD",
+      &[
+        ("E1M1", &[]),
+        ("THINGS", &[]),
+        ("LINEDEFS", &[]),
+        ("SIDEDEFS", &[]),
+        ("VERTEXES", &[1, 0, 2, 0, 0xFF, 0xFF, 4, 0]),
+        ("E1M2", &[]),
+        ("THINGS", &[]),
+        ("LINEDEFS", &[]),
+        ("SIDEDEFS", &[]),
+        ("VERTEXES", &[1, 0, 2, 0, 3, 0]),
+      ],
+    ))
+    .unwrap();
+
+    let vertexes: Vec<Vertex> = wad
+      .read_wad_for(wad.find_map_index("E1M1").unwrap())
+      .unwrap();
+    assert_eq!(vertexes.len(), 2);
+    assert_eq!((vertexes[1].x, vertexes[1].y), (-1, 4));
+    assert!(matches!(
+      wad.read_wad_for::<Vertex>(wad.find_map_index("E1M2").unwrap()),
+      Err(DoomError::LumpSizeMismatch {
+        size: 6,
+        record_size: 4,
+        ..
+      })
+    ));
+    assert!(matches!(
+      wad.read_wad_for::<Thing>(usize::MAX),
+      Err(DoomError::InvalidIndex { kind: "map", .. })
+    ));
+  }
+
+  #[test]
+  fn override_lumps_with_pwads() {
+    let mut wad = wad();
+    wad
+      .add_pwad(&build_wad(
+        "PWAD",
+        &[
+          ("FF_START", &[]),
+          ("FLOOR0_1", &[8]),
+          ("FF_END", &[]),
+          ("TROOA1", &[9]),
+        ],
+      ))
+      .unwrap();
+    assert_eq!(
+      wad.read_lump_in("FLOOR0_1", Namespace::Flats).unwrap(),
+      &[8]
+    );
+    assert_eq!(
+      wad.read_lump_in("TROOA1", Namespace::Sprites).unwrap(),
+      &[4]
+    );
+    assert_eq!(wad.read_lump("TROOA1").unwrap(), &[9]);
+    assert_eq!(wad.read_lump("PLAYPAL").unwrap(), &[1, 2, 3]);
+  }
+
+  #[test]
+  fn check_wad_type() {
+    assert!(matches!(
+      Wad::new(&build_wad("PWAD", &[])),
+      Err(DoomError::UnsupportedFormat(_))
+    ));
+    assert!(wad().add_pwad(&build_wad("IWAD", &[])).is_err());
+    assert!(matches!(
+      Wad::new(b"IWAD"),
+      Err(DoomError::Truncated { offset: 4, .. })
+    ));
+  }
+
+  // A tiny xorshift generator, so the fuzz tests are reproducible.
+  struct Random(u64);
+
+  impl Random {
+    fn next(&mut self) -> u64 {
+      self.0 ^= self.0 << 13;
+      self.0 ^= self.0 >> 7;
+      self.0 ^= self.0 << 17;
+      self.0
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+      (self.next() % max as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+      (0..len).map(|_| self.next() as u8).collect()
+    }
+  }
+
+  fn map_wad() -> Vec<u8> {
+    build_wad(
+      "IWAD",
+      &[
+        ("E1M1", &[]),
+        ("THINGS", &[0, 0, 0, 0, 90, 0, 1, 0, 7, 0]),
+        (
+          "LINEDEFS",
+          &[0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF],
+        ),
+        ("SIDEDEFS", &[b'S'; 30]),
+        ("VERTEXES", &[0, 0, 0, 0, 64, 0, 0, 0]),
+        ("SEGS", &[0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+        ("SSECTORS", &[1, 0, 0, 0]),
+        ("NODES", &[0; 28]),
+        ("SECTORS", &[b'F'; 26]),
+        ("REJECT", &[0]),
+        (
+          "BLOCKMAP",
+          &[0, 0, 0, 0, 1, 0, 1, 0, 5, 0, 0, 0, 0, 0, 0xFF, 0xFF],
+        ),
+      ],
+    )
+  }
+
+  fn read_everything(wad: &Wad) {
+    for index in 0..wad.directories().len() + 1 {
+      let _ = wad.lump(index);
+    }
+    for map_index in 0..wad.directories().len() + 1 {
+      let _ = wad.read_wad_for::<Thing>(map_index);
+      let _ = wad.read_wad_for::<LineDef>(map_index);
+      let _ = wad.read_wad_for::<SideDef>(map_index);
+      let _ = wad.read_wad_for::<Vertex>(map_index);
+      let _ = wad.read_wad_for::<Seg>(map_index);
+      let _ = wad.read_wad_for::<SSector>(map_index);
+      let _ = wad.read_wad_for::<Node>(map_index);
+      let _ = wad.read_wad_for::<Sector>(map_index);
+      let _ = wad.read_lump_for::<Blockmap>(map_index);
+      if let Ok(lump) = wad.read_map_lump(map_index, MapLumpsIndex::REJECT as usize, "REJECT") {
+        Reject::new(lump, 3).can_see(1, 2);
+      }
+    }
+    let _ = wad.read_wad_for::<Thing>(usize::MAX);
+    let _ = wad.read_lump_for::<Blockmap>(usize::MAX);
+  }
+
+  #[test]
+  fn read_map_wad() {
+    let wad = Wad::new(&map_wad()).unwrap();
+    let map_index = wad.find_map_index("E1M1").unwrap();
+    assert_eq!(wad.read_wad_for::<SideDef>(map_index).unwrap().len(), 1);
+    assert_eq!(wad.read_wad_for::<Sector>(map_index).unwrap().len(), 1);
+    assert_eq!(
+      wad.read_lump_for::<Blockmap>(map_index).unwrap().blocks,
+      vec![vec![0]]
+    );
+  }
+
+  #[test]
+  fn fuzz_random_buffers() {
+    let mut random = Random(0x2545_F491_4F6C_DD1D);
+    for _ in 0..2000 {
+      let len = random.below(64);
+      let buffer = random.bytes(len);
+      if let Ok(wad) = Wad::new(&buffer) {
+        read_everything(&wad);
+      }
+      let _ = Blockmap::read(&buffer);
+      let offset = random.below(8);
+      let _ = Thing::read(&buffer, offset);
+      let _ = SideDef::read(&buffer, offset);
+      let _ = Sector::read(&buffer, offset);
+      let _ = Node::read(&buffer, offset);
+      let _ = Seg::read(&buffer, usize::MAX - offset);
+    }
+  }
+
+  #[test]
+  fn fuzz_truncated_wads() {
+    let wad = map_wad();
+    for len in 0..wad.len() {
+      if let Ok(wad) = Wad::new(&wad[..len]) {
+        read_everything(&wad);
+      }
+    }
+  }
+
+  #[test]
+  fn fuzz_corrupted_wads() {
+    let mut random = Random(0x9E37_79B9_7F4A_7C15);
+    let valid = map_wad();
+    for _ in 0..5000 {
+      let mut wad = valid.clone();
+      for _ in 0..1 + random.below(4) {
+        let position = random.below(wad.len());
+        wad[position] = match random.below(3) {
+          0 => 0,
+          1 => 0xFF,
+          _ => random.next() as u8,
+        };
+      }
+      if let Ok(mut wad) = Wad::new(&wad) {
+        read_everything(&wad);
+        let _ = wad.add_pwad(&valid[..random.below(valid.len())]);
+        read_everything(&wad);
+      }
+    }
+  }
+}
@@ -0,0 +1,108 @@
+use crate::datatypes::Result;
+use crate::utils::{to_name, to_slice, to_u32};
+
+/// The size of one record of an ANIMATED lump.
+const ANIMATED_SIZE: usize = 23;
+/// Ends the list of animations.
+const END_OF_ANIMATIONS: u8 = 0xFF;
+/// Vanilla animations switch to their next frame every 8 tics.
+pub const ANIMATION_SPEED: usize = 8;
+
+/// An animated flat or texture. The frames are all lumps from `first` to
+/// `last`, in the order they are stored in the wad.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animated {
+  pub texture: bool,
+  pub first: String,
+  pub last: String,
+  pub speed: usize,
+}
+
+impl Animated {
+  /// The ANIMATED lump of Boom compatible PWADs replaces the built in
+  /// animations.
+  pub fn lump_name() -> String {
+    String::from("ANIMATED")
+  }
+
+  pub fn read_all(lump: &[u8]) -> Result<Vec<Animated>> {
+    let mut animations = Vec::new();
+    let mut offset = 0;
+    loop {
+      let typ = to_slice(lump, offset, 1)?[0];
+      if typ == END_OF_ANIMATIONS {
+        break;
+      }
+      animations.push(Animated {
+        texture: typ & 1 == 1,
+        last: to_name(lump, offset + 1)?.to_uppercase(),
+        first: to_name(lump, offset + 10)?.to_uppercase(),
+        speed: to_u32(lump, offset + 19)? as usize,
+      });
+      offset += ANIMATED_SIZE;
+    }
+    Ok(animations)
+  }
+
+  /// The flat animations built into the original engine.
+  pub fn flats() -> Vec<Animated> {
+    [
+      ("NUKAGE1", "NUKAGE3"),
+      ("FWATER1", "FWATER4"),
+      ("SWATER1", "SWATER4"),
+      ("LAVA1", "LAVA4"),
+      ("BLOOD1", "BLOOD3"),
+      ("RROCK05", "RROCK08"),
+      ("SLIME01", "SLIME04"),
+      ("SLIME05", "SLIME08"),
+      ("SLIME09", "SLIME12"),
+    ]
+    .iter()
+    .map(|(first, last)| Animated {
+      texture: false,
+      first: String::from(*first),
+      last: String::from(*last),
+      speed: ANIMATION_SPEED,
+    })
+    .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::wad::animated::Animated;
+
+  #[test]
+  fn read_animations() {
+    let mut lump = vec![0];
+    lump.extend(b"NUKAGE3\0\0NUKAGE1\0\0");
+    lump.extend(&[8, 0, 0, 0]);
+    lump.push(1);
+    lump.extend(b"blodgr4\0\0BLODGR1\0\0");
+    lump.extend(&[4, 0, 0, 0]);
+    lump.push(0xFF);
+    let animations = Animated::read_all(&lump).unwrap();
+    assert_eq!(
+      animations,
+      vec![
+        Animated {
+          texture: false,
+          first: String::from("NUKAGE1"),
+          last: String::from("NUKAGE3"),
+          speed: 8,
+        },
+        Animated {
+          texture: true,
+          first: String::from("BLODGR1"),
+          last: String::from("BLODGR4"),
+          speed: 4,
+        },
+      ]
+    );
+    assert!(matches!(
+      Animated::read_all(&lump[..lump.len() - 1]),
+      Err(DoomError::Truncated { offset: 46, .. })
+    ));
+  }
+}
@@ -0,0 +1,214 @@
+use crate::datatypes::{MapLumpsIndex, Result};
+use crate::errors::DoomError;
+use crate::utils::{to_i16, to_u16};
+use crate::wad::WadLump;
+
+const BLOCK_SIZE: i32 = 128;
+const BLOCK_LIST_START: u16 = 0x0000;
+const BLOCK_LIST_END: u16 = 0xFFFF;
+
+#[derive(Debug, Default)]
+pub struct Blockmap {
+  pub x_origin: i16,
+  pub y_origin: i16,
+  pub columns: u16,
+  pub rows: u16,
+  pub blocks: Vec<Vec<u16>>,
+}
+
+impl Blockmap {
+  fn read_block(lump: &[u8], offset: usize, has_markers: bool) -> Result<Vec<u16>> {
+    let mut offset = offset;
+    if has_markers {
+      offset += 2;
+    }
+    let mut line_defs = Vec::new();
+    loop {
+      let line_def = to_u16(lump, offset)?;
+      if line_def == BLOCK_LIST_END {
+        return Ok(line_defs);
+      }
+      line_defs.push(line_def);
+      offset += 2;
+    }
+  }
+
+  fn to_column(&self, x: i32) -> i32 {
+    (x - i32::from(self.x_origin)).div_euclid(BLOCK_SIZE)
+  }
+
+  fn to_row(&self, y: i32) -> i32 {
+    (y - i32::from(self.y_origin)).div_euclid(BLOCK_SIZE)
+  }
+
+  pub fn block_index(&self, x: i32, y: i32) -> Option<usize> {
+    let column = self.to_column(x);
+    let row = self.to_row(y);
+    if column < 0 || row < 0 || column >= i32::from(self.columns) || row >= i32::from(self.rows) {
+      return None;
+    }
+    Some((row * i32::from(self.columns) + column) as usize)
+  }
+
+  /// Returns every linedef stored in the blocks overlapping the given box,
+  /// sorted and without duplicates.
+  pub fn line_defs_in_box(&self, left: i32, bottom: i32, right: i32, top: i32) -> Vec<u16> {
+    let first_column = self.to_column(left).max(0);
+    let last_column = self.to_column(right).min(i32::from(self.columns) - 1);
+    let first_row = self.to_row(bottom).max(0);
+    let last_row = self.to_row(top).min(i32::from(self.rows) - 1);
+
+    let mut line_defs = Vec::new();
+    for row in first_row..=last_row {
+      for column in first_column..=last_column {
+        let index = (row * i32::from(self.columns) + column) as usize;
+        line_defs.extend_from_slice(&self.blocks[index]);
+      }
+    }
+    line_defs.sort_unstable();
+    line_defs.dedup();
+    line_defs
+  }
+}
+
+impl WadLump for Blockmap {
+  fn read(lump: &[u8]) -> Result<Self> {
+    let x_origin = to_i16(lump, 0)?;
+    let y_origin = to_i16(lump, 2)?;
+    let columns = to_u16(lump, 4)?;
+    let rows = to_u16(lump, 6)?;
+
+    let block_count = usize::from(columns) * usize::from(rows);
+    let header_size = block_count.saturating_mul(2).saturating_add(8);
+    if lump.len() < header_size {
+      return Err(DoomError::Truncated {
+        lump: Some(Blockmap::lump_name()),
+        offset: 8,
+        end: header_size,
+        actual: lump.len(),
+      });
+    }
+
+    let mut offsets = Vec::with_capacity(block_count);
+    for block in 0..block_count {
+      // Offsets are given in 16 bit words from the start of the lump.
+      let offset = usize::from(to_u16(lump, 8 + block * 2)?) * 2;
+      if offset < header_size {
+        return Err(DoomError::UnsupportedFormat(format!(
+          "Block {} of BLOCKMAP points into the header",
+          block
+        )));
+      }
+      offsets.push(offset);
+    }
+
+    // Most node builders put a 0 marker in front of every list, but some
+    // don't, in which case the first entry is a real linedef. A lump either
+    // has markers everywhere or nowhere.
+    let mut has_markers = true;
+    for &offset in offsets.iter() {
+      if to_u16(lump, offset)? != BLOCK_LIST_START {
+        has_markers = false;
+      }
+    }
+    let mut blocks = Vec::with_capacity(block_count);
+    for offset in offsets {
+      blocks.push(Blockmap::read_block(lump, offset, has_markers)?);
+    }
+
+    Ok(Blockmap {
+      x_origin,
+      y_origin,
+      columns,
+      rows,
+      blocks,
+    })
+  }
+
+  fn lump_name() -> String {
+    String::from("BLOCKMAP")
+  }
+
+  fn index() -> usize {
+    MapLumpsIndex::BLOCKMAP as usize
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::wad::blockmap::Blockmap;
+  use crate::wad::WadLump;
+
+  fn to_lump(words: &[u16]) -> Vec<u8> {
+    words
+      .iter()
+      .flat_map(|word| word.to_le_bytes().to_vec())
+      .collect()
+  }
+
+  // A 2x2 blockmap with its origin at (-128, 0).
+  fn blockmap() -> Blockmap {
+    let lump = to_lump(&[
+      (-128i16) as u16,
+      0,
+      2,
+      2,
+      8,
+      11,
+      14,
+      16,
+      0,
+      0,
+      0xFFFF,
+      0,
+      1,
+      0xFFFF,
+      0,
+      0xFFFF,
+      0,
+      1,
+      2,
+      0xFFFF,
+    ]);
+    Blockmap::read(&lump).unwrap()
+  }
+
+  #[test]
+  fn read_blocks() {
+    let blockmap = blockmap();
+    assert_eq!(blockmap.x_origin, -128);
+    assert_eq!(blockmap.columns, 2);
+    assert_eq!(blockmap.rows, 2);
+    assert_eq!(blockmap.blocks, vec![vec![0], vec![1], vec![], vec![1, 2]]);
+  }
+
+  #[test]
+  fn read_blocks_without_markers() {
+    // The first list starts with linedef 0, the second has no marker.
+    let lump = to_lump(&[0, 0, 2, 1, 6, 9, 0, 3, 0xFFFF, 2, 0xFFFF]);
+    let blockmap = Blockmap::read(&lump).unwrap();
+    assert_eq!(blockmap.blocks, vec![vec![0, 3], vec![2]]);
+  }
+
+  #[test]
+  fn block_index() {
+    let blockmap = blockmap();
+    assert_eq!(blockmap.block_index(-128, 0), Some(0));
+    assert_eq!(blockmap.block_index(0, 0), Some(1));
+    assert_eq!(blockmap.block_index(-1, 128), Some(2));
+    assert_eq!(blockmap.block_index(-129, 0), None);
+    assert_eq!(blockmap.block_index(128, 0), None);
+  }
+
+  #[test]
+  fn line_defs_in_box() {
+    let blockmap = blockmap();
+    assert_eq!(blockmap.line_defs_in_box(-10, 10, 10, 20), vec![0, 1]);
+    assert_eq!(
+      blockmap.line_defs_in_box(-1000, -1000, 1000, 1000),
+      vec![0, 1, 2]
+    );
+    assert_eq!(blockmap.line_defs_in_box(10, 130, 20, 140), vec![1, 2]);
+    assert!(blockmap.line_defs_in_box(500, 500, 600, 600).is_empty());
+  }
+}
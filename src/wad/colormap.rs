@@ -0,0 +1,97 @@
+use crate::datatypes::Result;
+use crate::errors::DoomError;
+
+/// Every colormap maps all 256 palette indexes onto other ones.
+pub const COLORMAP_SIZE: usize = 256;
+/// Maps 0 to 31 go from full brightness down to black.
+pub const LIGHT_LEVELS: usize = 32;
+/// The inverted grey map used while the player is invulnerable.
+pub const INVULNERABILITY: usize = 32;
+
+/// The COLORMAP lump holds the tables used for lighting. The vanilla lump
+/// has 34 maps, the last one is completely black and unused.
+#[derive(Debug)]
+pub struct ColorMap {
+  pub maps: Vec<u8>,
+}
+
+impl Default for ColorMap {
+  /// Maps every index onto itself, so nothing gets darker.
+  fn default() -> Self {
+    let identity = (0..COLORMAP_SIZE).map(|index| index as u8);
+    ColorMap {
+      maps: identity
+        .cycle()
+        .take((INVULNERABILITY + 1) * COLORMAP_SIZE)
+        .collect(),
+    }
+  }
+}
+
+impl ColorMap {
+  pub fn lump_name() -> String {
+    String::from("COLORMAP")
+  }
+
+  pub fn new(lump: &[u8]) -> Result<Self> {
+    if !lump.len().is_multiple_of(COLORMAP_SIZE) {
+      return Err(DoomError::LumpSizeMismatch {
+        lump: ColorMap::lump_name(),
+        size: lump.len(),
+        record_size: COLORMAP_SIZE,
+      });
+    }
+    let end = (INVULNERABILITY + 1) * COLORMAP_SIZE;
+    if lump.len() < end {
+      return Err(DoomError::Truncated {
+        lump: Some(ColorMap::lump_name()),
+        offset: lump.len(),
+        end,
+        actual: lump.len(),
+      });
+    }
+    Ok(ColorMap {
+      maps: Vec::from(lump),
+    })
+  }
+
+  pub fn map(&self, index: usize) -> &[u8] {
+    &self.maps[index * COLORMAP_SIZE..(index + 1) * COLORMAP_SIZE]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::wad::colormap::{ColorMap, COLORMAP_SIZE, INVULNERABILITY};
+
+  #[test]
+  fn read_colormaps() {
+    let lump = (0..34 * COLORMAP_SIZE)
+      .map(|i| (i / COLORMAP_SIZE) as u8)
+      .collect::<Vec<_>>();
+    let color_map = ColorMap::new(&lump).unwrap();
+    assert_eq!(color_map.map(0)[200], 0);
+    assert_eq!(color_map.map(31)[200], 31);
+    assert_eq!(color_map.map(INVULNERABILITY)[0], 32);
+  }
+
+  #[test]
+  fn default_to_identity() {
+    let color_map = ColorMap::default();
+    assert_eq!(color_map.map(0)[200], 200);
+    assert_eq!(color_map.map(INVULNERABILITY)[17], 17);
+  }
+
+  #[test]
+  fn reject_broken_lumps() {
+    assert!(matches!(
+      ColorMap::new(&[0; 300]),
+      Err(DoomError::LumpSizeMismatch { size: 300, .. })
+    ));
+    assert!(matches!(
+      ColorMap::new(&[0; 32 * COLORMAP_SIZE]),
+      Err(DoomError::Truncated { .. })
+    ));
+  }
+}
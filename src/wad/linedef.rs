@@ -3,6 +3,15 @@ use crate::utils::to_u16;
 use crate::wad::WadMetaData;
 use serde::{Deserialize, Serialize};
 
+/// Marks a missing back side on one sided linedefs.
+pub const NO_SIDE_DEF: u16 = 0xFFFF;
+
+/// The upper texture is drawn from the top down instead of from the bottom up.
+pub const UPPER_UNPEGGED: u16 = 0x0008;
+/// The lower and middle textures are aligned to the ceiling, respectively the
+/// floor, instead of the top of the texture.
+pub const LOWER_UNPEGGED: u16 = 0x0010;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LineDef {
   pub start_vertex: u16,
@@ -38,8 +47,8 @@ impl WadMetaData for LineDef {
     String::from("LINEDEFS")
   }
 
-  fn size_in_bytes() -> u32 {
-    std::mem::size_of::<LineDef>() as u32
+  fn size_in_bytes() -> usize {
+    14
   }
 
   fn index() -> usize {
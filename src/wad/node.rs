@@ -21,6 +21,35 @@ pub struct Node {
   pub left_child: u16,  // back
 }
 
+/// An axis aligned box around everything below a node child.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+  pub top: i16,
+  pub bottom: i16,
+  pub left: i16,
+  pub right: i16,
+}
+
+impl Node {
+  pub fn right_box(&self) -> BoundingBox {
+    BoundingBox {
+      top: self.right_box_top,
+      bottom: self.right_box_bottom,
+      left: self.right_box_left,
+      right: self.right_box_right,
+    }
+  }
+
+  pub fn left_box(&self) -> BoundingBox {
+    BoundingBox {
+      top: self.left_box_top,
+      bottom: self.left_box_bottom,
+      left: self.left_box_left,
+      right: self.left_box_right,
+    }
+  }
+}
+
 impl WadMetaData for Node {
   fn read(wad: &[u8], offset: usize) -> Result<Self> {
     let x_partition = to_i16(wad, offset)?;
@@ -59,8 +88,8 @@ impl WadMetaData for Node {
     String::from("NODES")
   }
 
-  fn size_in_bytes() -> u32 {
-    std::mem::size_of::<Node>() as u32
+  fn size_in_bytes() -> usize {
+    28
   }
 
   fn index() -> usize {
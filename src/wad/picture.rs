@@ -0,0 +1,184 @@
+use crate::datatypes::Result;
+use crate::utils::{to_i16, to_slice, to_u16, to_u32};
+
+/// Marks the end of a column.
+const END_OF_COLUMN: u8 = 0xFF;
+
+/// A vertical run of opaque pixels, starting `top_delta` rows below the top
+/// of the picture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Post {
+  pub top_delta: usize,
+  pub pixels: Vec<u8>,
+}
+
+/// The picture format used by sprites, wall patches and menu graphics. Every
+/// column is a list of posts, everything between them is transparent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+  pub width: usize,
+  pub height: usize,
+  pub left_offset: i16,
+  pub top_offset: i16,
+  pub columns: Vec<Vec<Post>>,
+}
+
+impl Picture {
+  pub fn new(lump: &[u8]) -> Result<Self> {
+    let width = usize::from(to_u16(lump, 0)?);
+    let height = usize::from(to_u16(lump, 2)?);
+    let left_offset = to_i16(lump, 4)?;
+    let top_offset = to_i16(lump, 6)?;
+
+    let mut columns = Vec::with_capacity(width);
+    for column in 0..width {
+      let mut offset = to_u32(lump, 8 + column * 4)? as usize;
+      let mut posts = Vec::new();
+      let mut top_delta = 0;
+      loop {
+        let delta = to_slice(lump, offset, 1)?[0];
+        if delta == END_OF_COLUMN {
+          break;
+        }
+        // Tall patches continue relative to the last post once the delta
+        // stops growing.
+        top_delta = match posts.last() {
+          Some(_) if usize::from(delta) <= top_delta => top_delta + usize::from(delta),
+          _ => usize::from(delta),
+        };
+        let length = usize::from(to_slice(lump, offset + 1, 1)?[0]);
+        // Each post is padded with an unused byte on both ends.
+        let pixels = to_slice(lump, offset + 3, length)?;
+        posts.push(Post {
+          top_delta,
+          pixels: Vec::from(pixels),
+        });
+        offset += length + 4;
+      }
+      columns.push(posts);
+    }
+
+    Ok(Picture {
+      width,
+      height,
+      left_offset,
+      top_offset,
+      columns,
+    })
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use crate::errors::DoomError;
+  use crate::wad::picture::{Picture, Post, END_OF_COLUMN};
+
+  /// Builds a picture lump whose columns are lists of `(top_delta, pixels)`
+  /// posts.
+  pub(crate) fn picture_lump(height: u16, columns: &[&[(u8, &[u8])]]) -> Vec<u8> {
+    let mut lump = Vec::from(&(columns.len() as u16).to_le_bytes()[..]);
+    lump.extend_from_slice(&height.to_le_bytes());
+    lump.extend_from_slice(&[0; 4]);
+    let mut posts = Vec::new();
+    for column in columns {
+      let offset = 8 + columns.len() * 4 + posts.len();
+      lump.extend_from_slice(&(offset as u32).to_le_bytes());
+      for (top_delta, pixels) in column.iter() {
+        posts.extend_from_slice(&[*top_delta, pixels.len() as u8, 0]);
+        posts.extend_from_slice(pixels);
+        posts.push(0);
+      }
+      posts.push(END_OF_COLUMN);
+    }
+    lump.extend(posts);
+    lump
+  }
+
+  #[test]
+  fn read_posts() {
+    // A 2x4 picture: the first column has two posts, the second is empty.
+    let mut lump = vec![2, 0, 4, 0, 1, 0, 2, 0, 16, 0, 0, 0, 27, 0, 0, 0];
+    lump.extend(&[0, 1, 0, 7, 0, 2, 2, 0, 8, 9, 0, 0xFF]);
+    lump.extend(&[0xFF]);
+    let picture = Picture::new(&lump).unwrap();
+    assert_eq!((picture.width, picture.height), (2, 4));
+    assert_eq!((picture.left_offset, picture.top_offset), (1, 2));
+    assert_eq!(
+      picture.columns[0],
+      vec![
+        Post {
+          top_delta: 0,
+          pixels: vec![7]
+        },
+        Post {
+          top_delta: 2,
+          pixels: vec![8, 9]
+        },
+      ]
+    );
+    assert!(picture.columns[1].is_empty());
+  }
+
+  #[test]
+  fn read_offsets() {
+    let mut lump = picture_lump(1, &[]);
+    lump[4..6].copy_from_slice(&(-3i16).to_le_bytes());
+    lump[6..8].copy_from_slice(&(-70i16).to_le_bytes());
+    let picture = Picture::new(&lump).unwrap();
+    assert_eq!((picture.width, picture.height), (0, 1));
+    assert_eq!((picture.left_offset, picture.top_offset), (-3, -70));
+    assert!(picture.columns.is_empty());
+  }
+
+  #[test]
+  fn read_tall_posts() {
+    let lump = picture_lump(300, &[&[(0, &[1]), (254, &[2]), (10, &[3]), (5, &[4])]]);
+    let picture = Picture::new(&lump).unwrap();
+    let top_deltas: Vec<_> = picture.columns[0]
+      .iter()
+      .map(|post| post.top_delta)
+      .collect();
+    assert_eq!(top_deltas, vec![0, 254, 264, 269]);
+  }
+
+  #[test]
+  fn read_shared_columns() {
+    // Identical columns may point to the same posts.
+    let mut lump = picture_lump(2, &[&[(1, &[5])], &[]]);
+    let first = Vec::from(&lump[8..12]);
+    lump[12..16].copy_from_slice(&first);
+    let picture = Picture::new(&lump).unwrap();
+    assert_eq!(picture.columns[0], picture.columns[1]);
+  }
+
+  #[test]
+  fn reject_truncated_pictures() {
+    let lump = picture_lump(4, &[&[(0, &[1, 2, 3])]]);
+    // The header,
+    assert!(matches!(
+      Picture::new(&lump[..6]),
+      Err(DoomError::Truncated { offset: 6, .. })
+    ));
+    // the column offsets,
+    assert!(matches!(
+      Picture::new(&lump[..10]),
+      Err(DoomError::Truncated { offset: 8, .. })
+    ));
+    // the pixels of a post
+    assert!(matches!(
+      Picture::new(&lump[..16]),
+      Err(DoomError::Truncated { offset: 15, .. })
+    ));
+    // and the end of a column are all required.
+    assert!(matches!(
+      Picture::new(&lump[..lump.len() - 1]),
+      Err(DoomError::Truncated { .. })
+    ));
+    let mut lump = lump;
+    lump[8] = 0xF0;
+    assert!(matches!(
+      Picture::new(&lump),
+      Err(DoomError::Truncated { offset: 240, .. })
+    ));
+  }
+}
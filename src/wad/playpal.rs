@@ -0,0 +1,109 @@
+use crate::datatypes::Result;
+use crate::errors::DoomError;
+
+/// Every palette has 256 colors with one byte for red, green and blue.
+pub const PALETTE_SIZE: usize = 256 * 3;
+/// Palette 0 is the normal one, 1 to 8 tint the screen red for damage and
+/// berserk, 9 to 12 flash yellow for pickups and 13 is the green radiation
+/// suit.
+pub const PALETTES: usize = 14;
+
+/// The PLAYPAL lump holds the palettes the indexed frame buffer gets
+/// converted with.
+#[derive(Debug)]
+pub struct PlayPal {
+  pub palettes: Vec<u8>,
+}
+
+impl Default for PlayPal {
+  /// A grey ramp for all palettes, so wads without a PLAYPAL still show
+  /// something.
+  fn default() -> Self {
+    let grey = (0..PALETTE_SIZE).map(|index| (index / 3) as u8);
+    PlayPal {
+      palettes: grey.cycle().take(PALETTES * PALETTE_SIZE).collect(),
+    }
+  }
+}
+
+impl PlayPal {
+  pub fn lump_name() -> String {
+    String::from("PLAYPAL")
+  }
+
+  pub fn new(lump: &[u8]) -> Result<Self> {
+    if !lump.len().is_multiple_of(PALETTE_SIZE) {
+      return Err(DoomError::LumpSizeMismatch {
+        lump: PlayPal::lump_name(),
+        size: lump.len(),
+        record_size: PALETTE_SIZE,
+      });
+    }
+    let end = PALETTES * PALETTE_SIZE;
+    if lump.len() < end {
+      return Err(DoomError::Truncated {
+        lump: Some(PlayPal::lump_name()),
+        offset: lump.len(),
+        end,
+        actual: lump.len(),
+      });
+    }
+    Ok(PlayPal {
+      palettes: Vec::from(lump),
+    })
+  }
+
+  pub fn palette(&self, index: usize) -> Result<&[u8]> {
+    if index >= PALETTES {
+      return Err(DoomError::InvalidIndex {
+        kind: "palette",
+        index,
+        max: PALETTES - 1,
+      });
+    }
+    Ok(&self.palettes[index * PALETTE_SIZE..(index + 1) * PALETTE_SIZE])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::wad::playpal::{PlayPal, PALETTES, PALETTE_SIZE};
+
+  #[test]
+  fn read_palettes() {
+    let lump = (0..PALETTES * PALETTE_SIZE)
+      .map(|i| (i / PALETTE_SIZE) as u8)
+      .collect::<Vec<_>>();
+    let play_pal = PlayPal::new(&lump).unwrap();
+    assert_eq!(play_pal.palette(0).unwrap()[5], 0);
+    assert_eq!(play_pal.palette(13).unwrap()[767], 13);
+  }
+
+  #[test]
+  fn default_to_grey() {
+    let play_pal = PlayPal::default();
+    assert_eq!(&play_pal.palette(0).unwrap()[300..303], &[100, 100, 100]);
+    assert_eq!(&play_pal.palette(13).unwrap()[765..768], &[255, 255, 255]);
+  }
+
+  #[test]
+  fn reject_broken_lumps_and_indexes() {
+    assert!(matches!(
+      PlayPal::new(&[0; 1000]),
+      Err(DoomError::LumpSizeMismatch { size: 1000, .. })
+    ));
+    assert!(matches!(
+      PlayPal::new(&[0; PALETTE_SIZE]),
+      Err(DoomError::Truncated { .. })
+    ));
+    assert!(matches!(
+      PlayPal::default().palette(14),
+      Err(DoomError::InvalidIndex {
+        index: 14,
+        max: 13,
+        ..
+      })
+    ));
+  }
+}
@@ -0,0 +1,45 @@
+use crate::datatypes::Result;
+use crate::utils::{to_name, to_u32};
+
+/// The PNAMES lump lists the names of all wall patches. Textures refer to
+/// their patches by the index into this list.
+#[derive(Debug, Default, PartialEq)]
+pub struct PNames {
+  pub names: Vec<String>,
+}
+
+impl PNames {
+  pub fn lump_name() -> String {
+    String::from("PNAMES")
+  }
+
+  pub fn new(lump: &[u8]) -> Result<Self> {
+    let count = to_u32(lump, 0)? as usize;
+    let mut names = Vec::new();
+    for index in 0..count {
+      names.push(to_name(lump, 4 + index * 8)?.to_uppercase());
+    }
+    Ok(PNames { names })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::errors::DoomError;
+  use crate::wad::pnames::PNames;
+
+  #[test]
+  fn read_names() {
+    let mut lump = vec![2, 0, 0, 0];
+    lump.extend(b"WALL00_1");
+    lump.extend(b"door2_4\0");
+    assert_eq!(
+      PNames::new(&lump).unwrap().names,
+      vec!["WALL00_1", "DOOR2_4"]
+    );
+    assert!(matches!(
+      PNames::new(&lump[..12]),
+      Err(DoomError::Truncated { offset: 12, .. })
+    ));
+  }
+}
@@ -0,0 +1,67 @@
+/// The REJECT lump is a bit matrix of sector_count × sector_count entries.
+/// A set bit at row `a`, column `b` means no monster in sector `a` can ever
+/// see sector `b`.
+#[derive(Debug, Default)]
+pub struct Reject {
+  pub sector_count: usize,
+  pub data: Vec<u8>,
+}
+
+impl Reject {
+  pub fn lump_name() -> String {
+    String::from("REJECT")
+  }
+
+  /// Many PWADs ship an empty, zeroed or too short REJECT lump. Everything
+  /// not covered by `lump` is treated as visible.
+  pub fn new(lump: &[u8], sector_count: usize) -> Self {
+    let size = (sector_count * sector_count).div_ceil(8);
+    Reject {
+      sector_count,
+      data: Vec::from(&lump[..lump.len().min(size)]),
+    }
+  }
+
+  pub fn can_see(&self, sector_a: usize, sector_b: usize) -> bool {
+    if sector_a >= self.sector_count || sector_b >= self.sector_count {
+      return true;
+    }
+    let bit = sector_a * self.sector_count + sector_b;
+    match self.data.get(bit / 8) {
+      Some(byte) => byte & (1 << (bit % 8)) == 0,
+      None => true,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::wad::reject::Reject;
+
+  #[test]
+  fn can_see() {
+    // 3 sectors: 0 can't see 2 (bit 2) and 2 can't see 0 (bit 6).
+    let reject = Reject::new(&[0b0100_0100, 0b0000_0000], 3);
+    assert!(!reject.can_see(0, 2));
+    assert!(!reject.can_see(2, 0));
+    assert!(reject.can_see(0, 1));
+    assert!(reject.can_see(1, 2));
+    assert!(reject.can_see(2, 2));
+  }
+
+  #[test]
+  fn truncated_lump_is_visible() {
+    let reject = Reject::new(&[0xFF], 4);
+    assert!(!reject.can_see(0, 3));
+    assert!(!reject.can_see(1, 3));
+    assert!(reject.can_see(2, 0));
+    assert!(reject.can_see(3, 3));
+  }
+
+  #[test]
+  fn missing_lump_is_visible() {
+    let reject = Reject::new(&[], 2);
+    assert!(reject.can_see(0, 1));
+    assert!(reject.can_see(5, 0));
+  }
+}
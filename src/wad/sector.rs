@@ -0,0 +1,78 @@
+use crate::datatypes::{MapLumpsIndex, Result};
+use crate::utils::{to_i16, to_name, to_u16};
+use crate::wad::WadMetaData;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Sector {
+  pub floor_height: i16,
+  pub ceiling_height: i16,
+  pub floor_texture: String,
+  pub ceiling_texture: String,
+  pub light_level: i16,
+  pub special_type: u16,
+  pub tag: u16,
+}
+
+impl WadMetaData for Sector {
+  fn read(wad: &[u8], offset: usize) -> Result<Self> {
+    let floor_height = to_i16(wad, offset)?;
+    let ceiling_height = to_i16(wad, offset + 2)?;
+    let floor_texture = to_name(wad, offset + 4)?;
+    let ceiling_texture = to_name(wad, offset + 12)?;
+    let light_level = to_i16(wad, offset + 20)?;
+    let special_type = to_u16(wad, offset + 22)?;
+    let tag = to_u16(wad, offset + 24)?;
+    Ok(Sector {
+      floor_height,
+      ceiling_height,
+      floor_texture,
+      ceiling_texture,
+      light_level,
+      special_type,
+      tag,
+    })
+  }
+
+  fn lump_name() -> String {
+    String::from("SECTORS")
+  }
+
+  fn size_in_bytes() -> usize {
+    26
+  }
+
+  fn index() -> usize {
+    MapLumpsIndex::SECTORS as usize
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::wad::sector::Sector;
+  use crate::wad::tests::read_map_records;
+
+  #[test]
+  fn read_sectors() {
+    let lump = [
+      &(-24i16).to_le_bytes()[..],
+      &128i16.to_le_bytes(),
+      b"NUKAGE1\0",
+      b"F_SKY1\0\0",
+      &160i16.to_le_bytes(),
+      &7u16.to_le_bytes(),
+      &3u16.to_le_bytes(),
+    ]
+    .concat();
+    let sectors: Vec<Sector> = read_map_records(&lump).unwrap();
+    assert_eq!(sectors.len(), 1);
+    assert_eq!(
+      (sectors[0].floor_height, sectors[0].ceiling_height),
+      (-24, 128)
+    );
+    assert_eq!(sectors[0].floor_texture, "NUKAGE1");
+    assert_eq!(sectors[0].ceiling_texture, "F_SKY1");
+    assert_eq!(sectors[0].light_level, 160);
+    assert_eq!((sectors[0].special_type, sectors[0].tag), (7, 3));
+  }
+}
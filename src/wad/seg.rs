@@ -1,6 +1,7 @@
 use crate::datatypes::MapLumpsIndex;
 use crate::errors::DoomError;
 use crate::utils::to_u16;
+use crate::wad::linedef::LineDef;
 use crate::wad::WadMetaData;
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +15,19 @@ pub struct Seg {
   pub offset: u16,
 }
 
+impl Seg {
+  /// Returns the sidedef facing the seg and the one on the other side of
+  /// its linedef.
+  pub fn side_defs(&self, line_defs: &[LineDef]) -> (u16, u16) {
+    let line_def = &line_defs[self.linedef as usize];
+    if self.side == 0 {
+      (line_def.front_side_def, line_def.back_side_def)
+    } else {
+      (line_def.back_side_def, line_def.front_side_def)
+    }
+  }
+}
+
 impl WadMetaData for Seg {
   fn read(wad: &[u8], offset: usize) -> Result<Self, DoomError> {
     let start_vertex = to_u16(wad, offset)?;
@@ -36,8 +50,8 @@ impl WadMetaData for Seg {
     String::from("SEGS")
   }
 
-  fn size_in_bytes() -> u32 {
-    std::mem::size_of::<Seg>() as u32
+  fn size_in_bytes() -> usize {
+    12
   }
 
   fn index() -> usize {
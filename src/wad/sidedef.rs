@@ -0,0 +1,71 @@
+use crate::datatypes::{MapLumpsIndex, Result};
+use crate::utils::{to_i16, to_name, to_u16};
+use crate::wad::WadMetaData;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SideDef {
+  pub x_offset: i16,
+  pub y_offset: i16,
+  pub upper_texture: String,
+  pub lower_texture: String,
+  pub middle_texture: String,
+  pub sector: u16,
+}
+
+impl WadMetaData for SideDef {
+  fn read(wad: &[u8], offset: usize) -> Result<Self> {
+    let x_offset = to_i16(wad, offset)?;
+    let y_offset = to_i16(wad, offset + 2)?;
+    let upper_texture = to_name(wad, offset + 4)?;
+    let lower_texture = to_name(wad, offset + 12)?;
+    let middle_texture = to_name(wad, offset + 20)?;
+    let sector = to_u16(wad, offset + 28)?;
+    Ok(SideDef {
+      x_offset,
+      y_offset,
+      upper_texture,
+      lower_texture,
+      middle_texture,
+      sector,
+    })
+  }
+
+  fn lump_name() -> String {
+    String::from("SIDEDEFS")
+  }
+
+  fn size_in_bytes() -> usize {
+    30
+  }
+
+  fn index() -> usize {
+    MapLumpsIndex::SIDEDEFS as usize
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::wad::sidedef::SideDef;
+  use crate::wad::tests::read_map_records;
+
+  #[test]
+  fn read_side_defs() {
+    let lump = [
+      &(-8i16).to_le_bytes()[..],
+      &16i16.to_le_bytes(),
+      b"STARTAN3",
+      b"-\0\0\0\0\0\0\0",
+      b"DOOR3\0\0\0",
+      &7u16.to_le_bytes(),
+    ]
+    .concat();
+    let side_defs: Vec<SideDef> = read_map_records(&lump).unwrap();
+    assert_eq!(side_defs.len(), 1);
+    assert_eq!((side_defs[0].x_offset, side_defs[0].y_offset), (-8, 16));
+    assert_eq!(side_defs[0].upper_texture, "STARTAN3");
+    assert_eq!(side_defs[0].lower_texture, "-");
+    assert_eq!(side_defs[0].middle_texture, "DOOR3");
+    assert_eq!(side_defs[0].sector, 7);
+  }
+}
@@ -24,8 +24,8 @@ impl WadMetaData for SSector {
     String::from("SSECTORS")
   }
 
-  fn size_in_bytes() -> u32 {
-    std::mem::size_of::<SSector>() as u32
+  fn size_in_bytes() -> usize {
+    4
   }
 
   fn index() -> usize {
@@ -0,0 +1,135 @@
+use crate::datatypes::Result;
+use crate::utils::{to_i16, to_name, to_u32};
+
+/// The size of a texture definition without its patches.
+const TEXTURE_SIZE: usize = 22;
+/// The size of one patch of a texture definition.
+const PATCH_SIZE: usize = 10;
+
+/// A patch placed on a texture. `patch` is the index into PNAMES.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TexturePatch {
+  pub origin_x: i16,
+  pub origin_y: i16,
+  pub patch: usize,
+}
+
+/// A wall texture of TEXTURE1 or TEXTURE2, made of one or more patches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureDef {
+  pub name: String,
+  pub width: usize,
+  pub height: usize,
+  pub patches: Vec<TexturePatch>,
+}
+
+impl TextureDef {
+  /// TEXTURE2 only exists in the registered and commercial IWADs.
+  pub fn lump_names() -> [&'static str; 2] {
+    ["TEXTURE1", "TEXTURE2"]
+  }
+
+  /// Reads all definitions of a TEXTURE1 or TEXTURE2 lump. The unused
+  /// masked flag, column directory, step direction and colormap fields are
+  /// skipped.
+  pub fn read_all(lump: &[u8]) -> Result<Vec<TextureDef>> {
+    let count = to_u32(lump, 0)? as usize;
+    let mut textures = Vec::new();
+    for index in 0..count {
+      let offset = to_u32(lump, 4 + index * 4)? as usize;
+      textures.push(TextureDef::read(lump, offset)?);
+    }
+    Ok(textures)
+  }
+
+  fn read(lump: &[u8], offset: usize) -> Result<TextureDef> {
+    let name = to_name(lump, offset)?.to_uppercase();
+    let width = to_i16(lump, offset + 12)?.max(0) as usize;
+    let height = to_i16(lump, offset + 14)?.max(0) as usize;
+    let patch_count = to_i16(lump, offset + 20)?.max(0) as usize;
+    let mut patches = Vec::with_capacity(patch_count);
+    for patch in 0..patch_count {
+      let patch_offset = offset + TEXTURE_SIZE + patch * PATCH_SIZE;
+      patches.push(TexturePatch {
+        origin_x: to_i16(lump, patch_offset)?,
+        origin_y: to_i16(lump, patch_offset + 2)?,
+        patch: to_i16(lump, patch_offset + 4)?.max(0) as usize,
+      });
+    }
+    Ok(TextureDef {
+      name,
+      width,
+      height,
+      patches,
+    })
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use crate::errors::DoomError;
+  use crate::wad::texture::{TextureDef, TexturePatch};
+
+  /// A texture as `(name, width, height, [(x, y, patch)])`.
+  pub(crate) type TextureEntry<'a> = (&'a str, i16, i16, &'a [(i16, i16, i16)]);
+
+  pub(crate) fn texture_lump(textures: &[TextureEntry]) -> Vec<u8> {
+    let mut lump = Vec::from(&(textures.len() as u32).to_le_bytes()[..]);
+    let mut definitions = Vec::new();
+    for (name, width, height, patches) in textures {
+      let offset = 4 + textures.len() * 4 + definitions.len();
+      lump.extend_from_slice(&(offset as u32).to_le_bytes());
+      let mut texture_name = [0u8; 8];
+      texture_name[..name.len()].copy_from_slice(name.as_bytes());
+      definitions.extend_from_slice(&texture_name);
+      definitions.extend_from_slice(&[0; 4]);
+      definitions.extend_from_slice(&width.to_le_bytes());
+      definitions.extend_from_slice(&height.to_le_bytes());
+      definitions.extend_from_slice(&[0; 4]);
+      definitions.extend_from_slice(&(patches.len() as i16).to_le_bytes());
+      for (x, y, patch) in patches.iter() {
+        definitions.extend_from_slice(&x.to_le_bytes());
+        definitions.extend_from_slice(&y.to_le_bytes());
+        definitions.extend_from_slice(&patch.to_le_bytes());
+        definitions.extend_from_slice(&[1, 0, 0, 0]);
+      }
+    }
+    lump.extend(definitions);
+    lump
+  }
+
+  #[test]
+  fn read_definitions() {
+    let lump = texture_lump(&[
+      ("STARTAN3", 128, 128, &[(0, 0, 0), (64, -8, 1)]),
+      ("sky1", 256, 128, &[(0, 0, 2)]),
+    ]);
+    let textures = TextureDef::read_all(&lump).unwrap();
+    assert_eq!(textures.len(), 2);
+    assert_eq!(
+      textures[0],
+      TextureDef {
+        name: String::from("STARTAN3"),
+        width: 128,
+        height: 128,
+        patches: vec![
+          TexturePatch {
+            origin_x: 0,
+            origin_y: 0,
+            patch: 0
+          },
+          TexturePatch {
+            origin_x: 64,
+            origin_y: -8,
+            patch: 1
+          },
+        ],
+      }
+    );
+    assert_eq!(textures[1].name, "SKY1");
+    assert!(matches!(
+      TextureDef::read_all(&lump[..lump.len() - 5]),
+      Err(DoomError::Truncated { .. })
+    ));
+  }
+}
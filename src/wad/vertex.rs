@@ -25,8 +25,8 @@ impl WadMetaData for Vertex {
     String::from("VERTEXES")
   }
 
-  fn size_in_bytes() -> u32 {
-    std::mem::size_of::<Vertex>() as u32
+  fn size_in_bytes() -> usize {
+    4
   }
 
   fn index() -> usize {
@@ -116,15 +116,32 @@ function clearScene (scene) {
   renderer.setSize(window.innerWidth, window.innerHeight)
   document.body.appendChild(renderer.domElement)
 
+  // The 3D view is drawn by rust into a 320x200 frame buffer which only needs
+  // to be copied into the canvas, the browser scales it up.
+  const screen = document.createElement('canvas')
+  screen.width = 320
+  screen.height = 200
+  screen.className = 'screen'
+  document.body.appendChild(screen)
+  const context = screen.getContext('2d')
+  const imageData = context.createImageData(screen.width, screen.height)
+
   const response = await fetch('./doomu.wad')
   const downloadedMap = await response.arrayBuffer()
 
   const doom = Doom.new(downloadedMap)
 
-  const map = doom.loadMap('E1M1')
+  const params = new URLSearchParams(window.location.search)
+  for (let pwad of params.getAll('pwad')) {
+    const response = await fetch(pwad)
+    doom.addPwad(await response.arrayBuffer())
+  }
+
+  const mapName = params.get('map') || 'E1M1'
+  const map = doom.loadMap(mapName)
   const xShift = -map.x_min - map.x_max / 2
   const yShift = -map.y_min + map.y_max / 2
-  doom.loadPlayer('E1M1', 1)
+  doom.loadPlayer(mapName, 1)
 
   const player = () => {
     const player = doom.getPlayer()
@@ -139,6 +156,7 @@ function clearScene (scene) {
 
   let pressedKey = ''
   let renderAutoMap = true
+  let palette = Number(params.get('palette')) || 0
 
   document.addEventListener('keydown', e => {
     pressedKey = e.key
@@ -146,6 +164,10 @@ function clearScene (scene) {
     if (e.key === 'Tab') {
       renderAutoMap = !renderAutoMap
     }
+
+    if (e.key === 'p') {
+      palette = (palette + 1) % 14
+    }
   })
 
   window.addEventListener('resize', () => {
@@ -154,9 +176,23 @@ function clearScene (scene) {
     renderer.setSize(window.innerWidth, window.innerHeight)
   }, false)
 
+  // The game runs at 35 tics per second like the original engine, however
+  // often the browser asks for a frame.
+  const ticDuration = 1000 / 35
+  const maxTicsPerFrame = 10
+  let lastTime = performance.now()
+  let elapsed = 0
+
   const animate = () => {
     stats.begin()
-    doom.tick(pressedKey)
+    const now = performance.now()
+    elapsed = Math.min(elapsed + now - lastTime, maxTicsPerFrame * ticDuration)
+    lastTime = now
+    while (elapsed >= ticDuration) {
+      doom.tick(pressedKey)
+      pressedKey = ''
+      elapsed -= ticDuration
+    }
     if (renderAutoMap) {
       addMap({ ...map, xShift, yShift }, scene)
       addPlayer({ ...player(), xShift, yShift }, scene)
@@ -165,7 +201,12 @@ function clearScene (scene) {
       clearScene(scene)
     }
     renderer.render(scene, camera)
-    pressedKey = ''
+    renderer.domElement.style.display = renderAutoMap ? 'block' : 'none'
+    screen.style.display = renderAutoMap ? 'none' : 'block'
+    if (!renderAutoMap) {
+      imageData.data.set(doom.getFrameBufferRgba(palette))
+      context.putImageData(imageData, 0, 0)
+    }
     stats.end()
     requestAnimationFrame(animate)
   }